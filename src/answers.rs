@@ -0,0 +1,194 @@
+use days::lib::{parse_token, Part};
+use error::{Error, Result};
+use runner::DayResult;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Kept next to the puzzle inputs, since the answers depend on them.
+pub const ANSWERS_FILENAME: &str = "answers.txt";
+
+/// Known good answers, one `<day> <part> <answer>` line per day and part.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Answers {
+    answers: BTreeMap<(usize, Part), String>,
+}
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut answers = BTreeMap::new();
+
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.splitn(3, ' ').collect();
+            if fields.len() != 3 || fields[2].is_empty() {
+                let message = format!("expected '<day> <part> <answer>', found '{}'", line);
+                return Err(Error::at(input, line, message));
+            }
+
+            let day = parse_token(input, fields[0], "a day number")?;
+            let part = match fields[1] {
+                "1" => Part::One,
+                "2" => Part::Two,
+                part => {
+                    let message = format!("expected part 1 or 2, found '{}'", part);
+                    return Err(Error::at(input, part, message));
+                }
+            };
+            answers.insert((day, part), fields[2].to_string());
+        }
+
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, day: usize, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part)).map(|answer| answer.as_str())
+    }
+
+    /// Stores every solved part of `result`, replacing what was there before.
+    pub fn record(&mut self, result: &DayResult) {
+        for part in result.parts() {
+            if let Some(answer) = &part.answer {
+                self.answers
+                    .insert((result.day, part.part), answer.to_string());
+            }
+        }
+    }
+
+    pub fn verify(&self, result: &DayResult) -> Vec<(Part, Verdict)> {
+        result
+            .parts()
+            .map(|part| {
+                let verdict = match (self.get(result.day, part.part), &part.answer) {
+                    (None, _) => Verdict::Missing,
+                    (Some(expected), Some(actual)) if expected == actual => Verdict::Pass,
+                    (Some(expected), actual) => Verdict::Fail {
+                        expected: expected.to_string(),
+                        actual: actual.clone(),
+                    },
+                };
+                (part.part, verdict)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((day, part), answer) in &self.answers {
+            writeln!(f, "{} {} {}", day, part.number(), answer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+        actual: Option<String>,
+    },
+    /// There is no expected answer to compare against.
+    Missing,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail {
+                expected,
+                actual: Some(actual),
+            } => write!(f, "fail (expected {}, got {})", expected, actual),
+            Verdict::Fail {
+                expected,
+                actual: None,
+            } => write!(f, "fail (expected {}, got no answer)", expected),
+            Verdict::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runner::PartResult;
+    use std::time::Duration;
+
+    fn day_result(day: usize, part_one: Option<&str>, part_two: Option<&str>) -> DayResult {
+        let part_result = |part, answer: Option<&str>| {
+            Some(PartResult {
+                part,
+                answer: answer.map(|answer| answer.to_string()),
+                solve_time: Duration::default(),
+            })
+        };
+        DayResult {
+            day,
+            read_time: Duration::default(),
+            parse_time: Duration::default(),
+            part_one: part_result(Part::One, part_one),
+            part_two: part_result(Part::Two, part_two),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("1 1 1006875\n21 2 lmzg,cxk\n\n").unwrap();
+
+        assert_eq!(Some("1006875"), answers.get(1, Part::One));
+        assert_eq!(Some("lmzg,cxk"), answers.get(21, Part::Two));
+        assert_eq!(None, answers.get(1, Part::Two));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                1,
+                "expected '<day> <part> <answer>', found '2 1'"
+            )),
+            Answers::parse("1 1 1006875\n2 1").err()
+        );
+        assert_eq!(
+            Some(Error::parse(1, 3, "expected part 1 or 2, found '3'")),
+            Answers::parse("1 3 1006875").err()
+        );
+        assert_eq!(
+            Some(Error::parse(1, 1, "expected a day number, found 'x'")),
+            Answers::parse("x 1 1006875").err()
+        );
+    }
+
+    #[test]
+    fn test_record_and_display() {
+        let mut answers = Answers::parse("2 1 643\n1 1 1").unwrap();
+        answers.record(&day_result(1, Some("1006875"), None));
+
+        assert_eq!("1 1 1006875\n2 1 643\n", answers.to_string());
+        assert_eq!(Ok(answers.clone()), Answers::parse(&answers.to_string()));
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse("1 1 1006875\n1 2 165026160\n20 1 20899048083289").unwrap();
+
+        assert_eq!(
+            vec![(Part::One, Verdict::Pass), (Part::Two, Verdict::Pass)],
+            answers.verify(&day_result(1, Some("1006875"), Some("165026160")))
+        );
+        assert_eq!(
+            vec![
+                (
+                    Part::One,
+                    Verdict::Fail {
+                        expected: "20899048083289".to_string(),
+                        actual: Some("1".to_string()),
+                    }
+                ),
+                (Part::Two, Verdict::Missing)
+            ],
+            answers.verify(&day_result(20, Some("1"), None))
+        );
+    }
+}
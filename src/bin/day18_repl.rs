@@ -0,0 +1,38 @@
+#![warn(clippy::all)]
+extern crate advent_of_code_2020;
+
+use advent_of_code_2020::repl::{run, Mode, Repl, HELP};
+use std::env;
+use std::io::{self, IsTerminal};
+use std::process;
+
+const USAGE: &str = "USAGE:
+    day18_repl [--mode left-to-right|addition-first|standard]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mode = match args.as_slice() {
+        [] => Mode::LeftToRight,
+        [option, mode] if option == "--mode" => match Mode::from_name(mode) {
+            Some(mode) => mode,
+            None => {
+                eprintln!("error: unknown mode '{}'\n\n{}", mode, USAGE);
+                process::exit(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    if interactive {
+        println!("{}\n", HELP);
+    }
+    if let Err(error) = run(Repl::new(mode), stdin.lock(), io::stdout(), interactive) {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,652 @@
+use days::lib::recorder::AnimationFormat;
+use days::lib::{InputKind, InputSource, Part};
+use output::OutputFormat;
+use std::fmt;
+
+pub const USAGE: &str = "USAGE:
+    advent_of_code_2020 [--inputs <dir>] <command>
+
+COMMANDS:
+    advent_of_code_2020 <day> [<path|->]
+    advent_of_code_2020 run <day|first-last> [--part 1|2] [--input <path|-> | --example] [--format text|json|csv] [--timings] [--parallel]
+    advent_of_code_2020 all [--part 1|2] [--format text|json|csv] [--timings] [--parallel]
+    advent_of_code_2020 bench <day|first-last> [--iterations <n>]
+    advent_of_code_2020 verify [<day|first-last>] [--answers <path>] [--parallel]
+    advent_of_code_2020 record [<day|first-last>] [--answers <path>]
+    advent_of_code_2020 animate <day> [--part 1|2] [--input <path|-> | --example] [--format ascii|ppm|gif] [--output <path>]
+    advent_of_code_2020 list
+    advent_of_code_2020 new <day>
+    advent_of_code_2020 help
+
+Puzzle inputs are read from --inputs, $AOC_INPUTS_DIR, ./inputs or the repository's inputs directory,
+in that order.";
+
+const DEFAULT_BENCH_ITERATIONS: usize = 10;
+
+/// Options that don't take a value.
+const FLAGS: &[&str] = &["--timings", "--parallel", "--example"];
+
+fn parse_part(part: &str) -> Option<Part> {
+    match part {
+        "1" => Some(Part::One),
+        "2" => Some(Part::Two),
+        _ => None,
+    }
+}
+
+fn parse_format(format: &str) -> Option<OutputFormat> {
+    match format {
+        "text" => Some(OutputFormat::Text),
+        "json" => Some(OutputFormat::Json),
+        "csv" => Some(OutputFormat::Csv),
+        _ => None,
+    }
+}
+
+fn parse_animation_format(format: &str) -> Option<AnimationFormat> {
+    match format {
+        "ascii" => Some(AnimationFormat::Ascii),
+        "ppm" => Some(AnimationFormat::Ppm),
+        "gif" => Some(AnimationFormat::Gif),
+        _ => None,
+    }
+}
+
+/// An inclusive range of day numbers, as given on the command line (`5` or `3-7`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DayRange {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl DayRange {
+    fn from_str(range: &str) -> Result<Self, CliError> {
+        let mut bounds = range.splitn(2, '-');
+        let first = parse_day_number(bounds.next().unwrap_or(""))?;
+        let last = match bounds.next() {
+            Some(last) => parse_day_number(last)?,
+            None => first,
+        };
+
+        if first > last {
+            return Err(CliError(format!(
+                "invalid day range '{}': {} comes after {}",
+                range, first, last
+            )));
+        }
+        Ok(DayRange { first, last })
+    }
+
+    pub fn contains(&self, day: usize) -> bool {
+        (self.first..=self.last).contains(&day)
+    }
+
+    pub fn is_single_day(&self) -> bool {
+        self.first == self.last
+    }
+}
+
+fn parse_day_number(day: &str) -> Result<usize, CliError> {
+    match day.parse::<usize>() {
+        Ok(day) if day >= 1 => Ok(day),
+        _ => Err(CliError(format!(
+            "expected a day number (1, 2, ...), got '{}'",
+            day
+        ))),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Command {
+    Run {
+        days: DayRange,
+        part: Option<Part>,
+        input: InputSource,
+        format: OutputFormat,
+        timings: bool,
+        parallel: bool,
+    },
+    All {
+        part: Option<Part>,
+        format: OutputFormat,
+        timings: bool,
+        parallel: bool,
+    },
+    Bench {
+        days: DayRange,
+        iterations: usize,
+    },
+    /// Compares answers against the expected answers file. `days` is `None` for every day.
+    /// `answers` is `None` for the answers file in the inputs directory.
+    Verify {
+        days: Option<DayRange>,
+        answers: Option<String>,
+        parallel: bool,
+    },
+    /// Stores the current answers in the expected answers file.
+    Record {
+        days: Option<DayRange>,
+        answers: Option<String>,
+    },
+    /// Records the frames of a day's simulation. `output` is `None` for the default file or
+    /// directory, or for stdout with the ASCII format.
+    Animate {
+        day: usize,
+        part: Part,
+        input: InputSource,
+        format: AnimationFormat,
+        output: Option<String>,
+    },
+    List,
+    /// Creates and registers a new day from the template.
+    New {
+        day: usize,
+    },
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CliError(pub String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The command to run, and the options shared by every command.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Args {
+    pub inputs_dir: Option<String>,
+    pub command: Command,
+}
+
+/// Parses the command line arguments, without the program name, including the global options
+/// that come before the command.
+pub fn parse_command_line(args: &[String]) -> Result<Args, CliError> {
+    let (inputs_dir, args) = match args.split_first() {
+        Some((option, args)) if option == "--inputs" => match args.split_first() {
+            Some((dir, args)) => (Some(dir.to_string()), args),
+            None => return Err(CliError("--inputs expects a value".to_string())),
+        },
+        _ => (None, args),
+    };
+
+    Ok(Args {
+        inputs_dir,
+        command: parse_args(args)?,
+    })
+}
+
+/// Parses a command and its arguments.
+pub fn parse_args(args: &[String]) -> Result<Command, CliError> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Err(CliError("missing command".to_string())),
+    };
+
+    match command {
+        "run" => parse_run(args),
+        "all" => {
+            let options = Options::parse(args, &["--part", "--format", "--timings", "--parallel"])?;
+            Ok(Command::All {
+                part: options.part()?,
+                format: options.format()?,
+                timings: options.timings()?,
+                parallel: options.flag("--parallel"),
+            })
+        }
+        "bench" => {
+            let (days, options) = parse_days_and_options(args, &["--iterations"])?;
+            Ok(Command::Bench {
+                days,
+                iterations: options.iterations()?,
+            })
+        }
+        "verify" => {
+            let (days, options) =
+                parse_optional_days_and_options(args, &["--answers", "--parallel"])?;
+            Ok(Command::Verify {
+                days,
+                answers: options.value("--answers").map(|x| x.to_string()),
+                parallel: options.flag("--parallel"),
+            })
+        }
+        "record" => {
+            let (days, options) = parse_optional_days_and_options(args, &["--answers"])?;
+            Ok(Command::Record {
+                days,
+                answers: options.value("--answers").map(|x| x.to_string()),
+            })
+        }
+        "animate" => {
+            let (days, options) = parse_days_and_options(
+                args,
+                &["--part", "--input", "--example", "--format", "--output"],
+            )?;
+            if !days.is_single_day() {
+                return Err(CliError("animate expects a single day".to_string()));
+            }
+            Ok(Command::Animate {
+                day: days.first,
+                part: options.part()?.unwrap_or(Part::One),
+                input: options.input(&days)?,
+                format: options.animation_format()?,
+                output: options.value("--output").map(|x| x.to_string()),
+            })
+        }
+        "list" => Options::parse(args, &[]).map(|_| Command::List),
+        "new" => match args {
+            [day] => Ok(Command::New {
+                day: parse_day_number(day)?,
+            }),
+            _ => Err(CliError("new expects a day number".to_string())),
+        },
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        day if day.parse::<usize>().is_ok() => {
+            let mut run_args = vec![day.to_string()];
+            match args.split_first() {
+                Some((input, options)) if !input.starts_with("--") => {
+                    run_args.push("--input".to_string());
+                    run_args.push(input.to_string());
+                    run_args.extend_from_slice(options);
+                }
+                _ => run_args.extend_from_slice(args),
+            }
+            parse_run(&run_args)
+        }
+        command => Err(CliError(format!("unknown command '{}'", command))),
+    }
+}
+
+fn parse_run(args: &[String]) -> Result<Command, CliError> {
+    let (days, options) = parse_days_and_options(
+        args,
+        &[
+            "--part",
+            "--input",
+            "--example",
+            "--format",
+            "--timings",
+            "--parallel",
+        ],
+    )?;
+    Ok(Command::Run {
+        part: options.part()?,
+        input: options.input(&days)?,
+        days,
+        format: options.format()?,
+        timings: options.timings()?,
+        parallel: options.flag("--parallel"),
+    })
+}
+
+fn parse_days_and_options<'a>(
+    args: &'a [String],
+    allowed_options: &[&str],
+) -> Result<(DayRange, Options<'a>), CliError> {
+    match args.split_first() {
+        Some((days, args)) if !days.starts_with("--") => Ok((
+            DayRange::from_str(days)?,
+            Options::parse(args, allowed_options)?,
+        )),
+        _ => Err(CliError("missing day number".to_string())),
+    }
+}
+
+fn parse_optional_days_and_options<'a>(
+    args: &'a [String],
+    allowed_options: &[&str],
+) -> Result<(Option<DayRange>, Options<'a>), CliError> {
+    match args.split_first() {
+        Some((days, args)) if !days.starts_with("--") => Ok((
+            Some(DayRange::from_str(days)?),
+            Options::parse(args, allowed_options)?,
+        )),
+        _ => Ok((None, Options::parse(args, allowed_options)?)),
+    }
+}
+
+struct Options<'a> {
+    values: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Options<'a> {
+    fn parse(args: &'a [String], allowed_options: &[&str]) -> Result<Self, CliError> {
+        let mut values = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(option) = args.next() {
+            if !allowed_options.contains(&option.as_str()) {
+                return Err(CliError(format!("unexpected argument '{}'", option)));
+            }
+            if FLAGS.contains(&option.as_str()) {
+                values.push((option.as_str(), ""));
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| CliError(format!("{} expects a value", option)))?;
+            values.push((option.as_str(), value.as_str()));
+        }
+
+        Ok(Options { values })
+    }
+
+    fn value(&self, option: &str) -> Option<&'a str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == option)
+            .map(|(_, value)| *value)
+    }
+
+    fn part(&self) -> Result<Option<Part>, CliError> {
+        match self.value("--part") {
+            Some(part) => parse_part(part)
+                .map(Some)
+                .ok_or_else(|| CliError(format!("--part expects 1 or 2, got '{}'", part))),
+            None => Ok(None),
+        }
+    }
+
+    fn format(&self) -> Result<OutputFormat, CliError> {
+        match self.value("--format") {
+            Some(format) => parse_format(format).ok_or_else(|| {
+                CliError(format!(
+                    "--format expects text, json or csv, got '{}'",
+                    format
+                ))
+            }),
+            None => Ok(OutputFormat::Text),
+        }
+    }
+
+    fn animation_format(&self) -> Result<AnimationFormat, CliError> {
+        match self.value("--format") {
+            Some(format) => parse_animation_format(format).ok_or_else(|| {
+                CliError(format!(
+                    "--format expects ascii, ppm or gif, got '{}'",
+                    format
+                ))
+            }),
+            None => Ok(AnimationFormat::Ascii),
+        }
+    }
+
+    fn input(&self, days: &DayRange) -> Result<InputSource, CliError> {
+        match (self.value("--input"), self.flag("--example")) {
+            (Some(_), _) if !days.is_single_day() => Err(CliError(
+                "--input can only be used when running a single day".to_string(),
+            )),
+            (Some(_), true) => Err(CliError(
+                "--input and --example can't be used together".to_string(),
+            )),
+            (Some(input), false) => Ok(InputSource::from_arg(input)),
+            (None, true) => Ok(InputSource::Puzzle(InputKind::Example)),
+            (None, false) => Ok(InputSource::Puzzle(InputKind::Real)),
+        }
+    }
+
+    fn flag(&self, flag: &str) -> bool {
+        self.value(flag).is_some()
+    }
+
+    fn timings(&self) -> Result<bool, CliError> {
+        let timings = self.flag("--timings");
+        if timings && self.format()? != OutputFormat::Text {
+            return Err(CliError(
+                "--timings can only be used with the text format".to_string(),
+            ));
+        }
+        Ok(timings)
+    }
+
+    fn iterations(&self) -> Result<usize, CliError> {
+        match self.value("--iterations") {
+            Some(iterations) => match iterations.parse::<usize>() {
+                Ok(iterations) if iterations > 0 => Ok(iterations),
+                _ => Err(CliError(format!(
+                    "--iterations expects a positive number, got '{}'",
+                    iterations
+                ))),
+            },
+            None => Ok(DEFAULT_BENCH_ITERATIONS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_day_shorthand() {
+        assert_eq!(
+            Ok(Command::Run {
+                days: DayRange { first: 5, last: 5 },
+                part: None,
+                input: InputSource::Puzzle(InputKind::Real),
+                format: OutputFormat::Text,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("5"))
+        );
+        assert_eq!(
+            Ok(Command::Run {
+                days: DayRange { first: 5, last: 5 },
+                part: Some(Part::One),
+                input: InputSource::File("my_input.txt".to_string()),
+                format: OutputFormat::Text,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("5 my_input.txt --part 1"))
+        );
+    }
+
+    #[test]
+    fn test_parse_run() {
+        assert_eq!(
+            Ok(Command::Run {
+                days: DayRange { first: 3, last: 7 },
+                part: Some(Part::Two),
+                input: InputSource::Puzzle(InputKind::Real),
+                format: OutputFormat::Text,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("run 3-7 --part 2"))
+        );
+        assert_eq!(
+            Ok(Command::Run {
+                days: DayRange { first: 1, last: 1 },
+                part: None,
+                input: InputSource::Stdin,
+                format: OutputFormat::Text,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("run 1 --input -"))
+        );
+        assert_eq!(
+            Ok(Command::Run {
+                days: DayRange { first: 2, last: 2 },
+                part: None,
+                input: InputSource::File("other.txt".to_string()),
+                format: OutputFormat::Csv,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("2 other.txt --format csv"))
+        );
+    }
+
+    #[test]
+    fn test_parse_example() {
+        assert_eq!(
+            Ok(Command::Run {
+                days: DayRange { first: 1, last: 9 },
+                part: None,
+                input: InputSource::Puzzle(InputKind::Example),
+                format: OutputFormat::Text,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("run 1-9 --example"))
+        );
+    }
+
+    #[test]
+    fn test_parse_command_line() {
+        assert_eq!(
+            Ok(Args {
+                inputs_dir: Some("../my_inputs".to_string()),
+                command: Command::List,
+            }),
+            parse_command_line(&args("--inputs ../my_inputs list"))
+        );
+        assert_eq!(
+            Ok(Args {
+                inputs_dir: None,
+                command: Command::List,
+            }),
+            parse_command_line(&args("list"))
+        );
+    }
+
+    #[test]
+    fn test_parse_other_commands() {
+        assert_eq!(
+            Ok(Command::All {
+                part: Some(Part::One),
+                format: OutputFormat::Text,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("all --part 1"))
+        );
+        assert_eq!(
+            Ok(Command::All {
+                part: None,
+                format: OutputFormat::Json,
+                timings: false,
+                parallel: false,
+            }),
+            parse_args(&args("all --format json"))
+        );
+        assert_eq!(
+            Ok(Command::All {
+                part: None,
+                format: OutputFormat::Text,
+                timings: true,
+                parallel: true,
+            }),
+            parse_args(&args("all --timings --parallel"))
+        );
+        assert_eq!(
+            Ok(Command::Bench {
+                days: DayRange {
+                    first: 15,
+                    last: 15
+                },
+                iterations: DEFAULT_BENCH_ITERATIONS,
+            }),
+            parse_args(&args("bench 15"))
+        );
+        assert_eq!(
+            Ok(Command::Bench {
+                days: DayRange { first: 1, last: 3 },
+                iterations: 50,
+            }),
+            parse_args(&args("bench 1-3 --iterations 50"))
+        );
+        assert_eq!(
+            Ok(Command::Verify {
+                days: None,
+                answers: None,
+                parallel: false,
+            }),
+            parse_args(&args("verify"))
+        );
+        assert_eq!(
+            Ok(Command::Verify {
+                days: Some(DayRange { first: 1, last: 5 }),
+                answers: None,
+                parallel: true,
+            }),
+            parse_args(&args("verify 1-5 --parallel"))
+        );
+        assert_eq!(
+            Ok(Command::Record {
+                days: Some(DayRange { first: 1, last: 3 }),
+                answers: Some("my_answers.txt".to_string()),
+            }),
+            parse_args(&args("record 1-3 --answers my_answers.txt"))
+        );
+        assert_eq!(
+            Ok(Command::Animate {
+                day: 11,
+                part: Part::One,
+                input: InputSource::Puzzle(InputKind::Real),
+                format: AnimationFormat::Ascii,
+                output: None,
+            }),
+            parse_args(&args("animate 11"))
+        );
+        assert_eq!(
+            Ok(Command::Animate {
+                day: 17,
+                part: Part::Two,
+                input: InputSource::Puzzle(InputKind::Example),
+                format: AnimationFormat::Gif,
+                output: Some("day17.gif".to_string()),
+            }),
+            parse_args(&args(
+                "animate 17 --part 2 --example --format gif --output day17.gif"
+            ))
+        );
+        assert_eq!(Ok(Command::List), parse_args(&args("list")));
+        assert_eq!(Ok(Command::New { day: 22 }), parse_args(&args("new 22")));
+        assert_eq!(Ok(Command::Help), parse_args(&args("help")));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_args(&args("")).is_err());
+        assert!(parse_args(&args("foo")).is_err());
+        assert!(parse_args(&args("run")).is_err());
+        assert!(parse_args(&args("run x")).is_err());
+        assert!(parse_args(&args("run 0")).is_err());
+        assert!(parse_args(&args("run 7-3")).is_err());
+        assert!(parse_args(&args("run 1 --part 3")).is_err());
+        assert!(parse_args(&args("run 1 --part")).is_err());
+        assert!(parse_args(&args("run 1-3 --input foo.txt")).is_err());
+        assert!(parse_args(&args("run 1 --input foo.txt --example")).is_err());
+        assert!(parse_command_line(&args("--inputs")).is_err());
+        assert!(parse_args(&args("run 1 --format xml")).is_err());
+        assert!(parse_args(&args("run 1 --timings --format csv")).is_err());
+        assert!(parse_args(&args("bench 1 --iterations 0")).is_err());
+        assert!(parse_args(&args("list --part 1")).is_err());
+        assert!(parse_args(&args("verify --part 1")).is_err());
+        assert!(parse_args(&args("animate 11-17")).is_err());
+        assert!(parse_args(&args("animate 11 --format json")).is_err());
+        assert!(parse_args(&args("new")).is_err());
+        assert!(parse_args(&args("new 22 23")).is_err());
+    }
+
+    #[test]
+    fn test_day_range() {
+        let range = DayRange::from_str("3-7").unwrap();
+        assert!(range.contains(3));
+        assert!(range.contains(7));
+        assert!(!range.contains(8));
+        assert!(!range.is_single_day());
+        assert!(DayRange::from_str("4").unwrap().is_single_day());
+    }
+}
@@ -20,3 +20,83 @@ pub mod day7;
 pub mod day8;
 pub mod day9;
 pub mod lib;
+
+use self::lib::{Result, Solution};
+
+/// Every solved day, in order. This is the only list `main.rs`, the benches
+/// and the tests need to know about.
+pub const DAYS: &[Day] = &[
+    Day::new(1, parse::<day1::Day1>),
+    Day::new(2, parse::<day2::Day2>),
+    Day::new(3, parse::<day3::Day3>),
+    Day::new(4, parse::<day4::Day4>),
+    Day::new(5, parse::<day5::Day5>),
+    Day::new(6, parse::<day6::Day6>),
+    Day::new(7, parse::<day7::Day7>),
+    Day::new(8, parse::<day8::Day8>),
+    Day::new(9, parse::<day9::Day9>),
+    Day::new(10, parse::<day10::Day10>),
+    Day::new(11, parse::<day11::Day11>),
+    Day::new(12, parse::<day12::Day12>),
+    Day::new(13, parse::<day13::Day13>),
+    Day::new(14, parse::<day14::Day14>),
+    Day::new(15, parse::<day15::Day15>),
+    Day::new(16, parse::<day16::Day16>),
+    Day::new(17, parse::<day17::Day17>),
+    Day::new(18, parse::<day18::Day18>),
+    Day::new(19, parse::<day19::Day19>),
+    Day::new(20, parse::<day20::Day20>),
+    Day::new(21, parse::<day21::Day21>),
+];
+
+pub fn get_day(number: usize) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+pub struct Day {
+    pub number: usize,
+    parse_fn: fn(&str) -> Result<Box<dyn Solution>>,
+}
+
+impl Day {
+    const fn new(number: usize, parse_fn: fn(&str) -> Result<Box<dyn Solution>>) -> Self {
+        Day { number, parse_fn }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Solution>> {
+        (self.parse_fn)(input)
+    }
+
+    pub fn run(&self, input: &str) -> Result<(Option<String>, Option<String>)> {
+        let solution = self.parse(input)?;
+        Ok((solution.try_part_one()?, solution.try_part_two()?))
+    }
+}
+
+fn parse<S: Solution + 'static>(input: &str) -> Result<Box<dyn Solution>> {
+    let solution = S::parse(input)?;
+    Ok(Box::new(solution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_are_sorted_and_unique() {
+        let numbers: Vec<usize> = DAYS.iter().map(|day| day.number).collect();
+        let mut expected_numbers = numbers.clone();
+        expected_numbers.sort_unstable();
+        expected_numbers.dedup();
+
+        assert_eq!(expected_numbers, numbers);
+    }
+
+    #[test]
+    fn test_get_day() {
+        assert_eq!(Some(1), get_day(1).map(|day| day.number));
+        assert_eq!(Some(21), get_day(21).map(|day| day.number));
+        assert!(get_day(0).is_none());
+        assert!(get_day(26).is_none());
+    }
+}
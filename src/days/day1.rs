@@ -1,21 +1,24 @@
 use super::lib::*;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day1.txt";
-    let inputs = read_inputs(&filename);
-    let inputs: Vec<i64> = inputs
-        .lines_of::<i64>()
-        .into_iter()
-        .filter_map(|x| x)
-        .collect();
-
-    let entries = find_two_entries_that_sum_to(&inputs, &2020).unwrap();
-    let part_one = multiply_entries(&entries);
-
-    let entries = find_three_entries_that_sum_to(&inputs, &2020).unwrap();
-    let part_two = multiply_entries(&entries);
-
-    (Some(part_one.to_string()), Some(part_two.to_string()))
+pub struct Day1 {
+    entries: Vec<i64>,
+}
+
+impl Solution for Day1 {
+    fn parse(input: &str) -> Result<Self> {
+        let entries = parse_lines(input, "an integer")?;
+        Ok(Day1 { entries })
+    }
+
+    fn part_one(&self) -> Option<String> {
+        find_two_entries_that_sum_to(&self.entries, &2020)
+            .map(|entries| multiply_entries(&entries).to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        find_three_entries_that_sum_to(&self.entries, &2020)
+            .map(|entries| multiply_entries(&entries).to_string())
+    }
 }
 
 fn multiply_entries(entries: &[i64]) -> i64 {
@@ -5,27 +5,31 @@ use std::collections::HashMap;
 extern crate itertools;
 use self::itertools::sorted;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day10.txt";
-    let inputs = read_inputs(&filename);
+pub struct Day10 {
+    adapters: Vec<usize>,
+}
 
-    let mut inputs: Vec<usize> = inputs
-        .lines()
-        .map(|line| line.parse::<usize>().unwrap())
-        .collect();
-    inputs.push(0);
-    inputs.push(inputs.iter().max().unwrap() + 3);
+impl Solution for Day10 {
+    fn parse(input: &str) -> Result<Self> {
+        let mut adapters: Vec<usize> = parse_lines(input, "a positive integer")?;
+        adapters.push(0);
+        adapters.push(adapters.iter().max().unwrap() + 3);
+        Ok(Day10 { adapters })
+    }
 
-    let jumps = get_jumps_for_longest_path(&inputs);
-    let part_one = Some((jumps.0 * jumps.1).to_string());
-    let part_two = Some(get_number_of_paths(&inputs).to_string());
+    fn part_one(&self) -> Option<String> {
+        let jumps = get_jumps_for_longest_path(&self.adapters);
+        Some((jumps.0 * jumps.1).to_string())
+    }
 
-    (part_one, part_two)
+    fn part_two(&self) -> Option<String> {
+        Some(get_number_of_paths(&self.adapters).to_string())
+    }
 }
 
 fn get_jumps_for_longest_path(inputs: &[usize]) -> (usize, usize) {
     let jumps = sorted(inputs.iter()).fold(
-        (0, 0, 0 as usize),
+        (0, 0, 0_usize),
         |(one_jumps, three_jumps, last_adapter), adapter| match adapter - last_adapter {
             1 => (one_jumps + 1, three_jumps, *adapter),
             3 => (one_jumps, three_jumps + 1, *adapter),
@@ -45,7 +49,7 @@ fn get_number_of_paths(inputs: &[usize]) -> usize {
         .skip(1)
         .fold(initial_paths_for_node, |mut paths_for_node, node| {
             let number_of_paths = (1..=3).fold(0, |acc, index| {
-                acc + paths_for_node.get(&(node + index)).or(Some(&0)).unwrap()
+                acc + paths_for_node.get(&(node + index)).unwrap_or(&0)
             });
             paths_for_node.insert(*node, number_of_paths);
             paths_for_node
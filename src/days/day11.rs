@@ -1,256 +1,130 @@
+use super::lib::dense::{DenseAutomaton, Neighbourhood, Neighbours};
 use super::lib::*;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day11.txt";
-    let inputs = read_inputs(&filename);
-
-    let initial_grid = Grid::from_str(&inputs);
-    let part_one_grid = initial_grid.run_until_stable(Grid::get_next_position);
-    let part_one = Some(part_one_grid.number_of_occupied_positions().to_string());
-    let part_two_grid = initial_grid.run_until_stable(Grid::get_next_position_advanced);
-    let part_two = Some(part_two_grid.number_of_occupied_positions().to_string());
-
-    (part_one, part_two)
-}
-
-#[derive(Debug, Eq, PartialEq, Clone)]
-struct Grid {
-    map: Vec<Vec<PositionType>>,
+pub struct Day11 {
+    initial_layout: Grid<PositionType>,
 }
 
-impl Grid {
-    fn width(&self) -> usize {
-        self.map.get(0).unwrap().len()
-    }
-
-    fn height(&self) -> usize {
-        self.map.len()
-    }
-
-    fn get(&self, row: &usize, col: &usize) -> Option<&PositionType> {
-        match self.map.get(*row) {
-            Some(r) => r.get(*col),
-            _ => None,
+impl Solution for Day11 {
+    fn parse(input: &str) -> Result<Self> {
+        if input.trim().is_empty() {
+            return Err(Error::input("expected a seat layout"));
         }
+        Ok(Day11 {
+            initial_layout: parse_layout(input)?,
+        })
     }
 
-    fn adjacent_occupied_seats(&self, row: &usize, col: &usize) -> usize {
-        let positions = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-
-        positions
-            .iter()
-            .map(|position| (*row as i64 + position.0, *col as i64 + position.1))
-            .filter(|position| position.0 >= 0 && position.1 >= 0)
-            .filter_map(|position| self.get(&(position.0 as usize), &(position.1 as usize)))
-            .filter(|position_type| **position_type == PositionType::OCCUPIED)
-            .count()
+    fn part_one(&self) -> Option<String> {
+        let mut seating = self.seating(Part::One);
+        seating.run_until_stable(&|position, neighbours| next_position(position, neighbours, 4));
+        Some(number_of_occupied_positions(seating.cells()).to_string())
     }
 
-    fn viewed_occupied_seats(&self, row: &usize, col: &usize) -> usize {
-        let mut positions: Vec<Vec<(usize, usize)>> = Vec::new();
-
-        positions.push(
-            (0..*row)
-                .rev()
-                .map(|position_row| (position_row, *col))
-                .collect(),
-        );
-        positions.push(
-            (row + 1..self.height())
-                .map(|position_row| (position_row, *col))
-                .collect(),
-        );
-        positions.push(
-            (0..*col)
-                .rev()
-                .map(|position_col| (*row, position_col))
-                .collect(),
-        );
-        positions.push(
-            (col + 1..self.width())
-                .map(|position_col| (*row, position_col))
-                .collect(),
-        );
-        positions.push(
-            (1..)
-                .take_while(|x| row + x <= self.width() && col + x <= self.height())
-                .map(|i| (*row + i, *col + i))
-                .collect(),
-        );
-        positions.push(
-            (1..)
-                .take_while(|x| row >= x && col >= x)
-                .map(|i| (*row - i, *col - i))
-                .collect(),
-        );
-        positions.push(
-            (1..)
-                .take_while(|x| row >= x && col + x <= self.height())
-                .map(|i| (*row - i, *col + i))
-                .collect(),
-        );
-        positions.push(
-            (1..)
-                .take_while(|x| row + x <= self.width() && col >= x)
-                .map(|i| (*row + i, *col - i))
-                .collect(),
-        );
-        positions
-            .iter()
-            .filter_map(|positions| {
-                let found_position = positions
-                    .iter()
-                    .filter_map(|position| self.get(&position.0, &position.1))
-                    .find(|position_type| **position_type != PositionType::FLOOR);
-
-                match found_position {
-                    Some(PositionType::OCCUPIED) => Some(PositionType::OCCUPIED),
-                    _ => None,
-                }
-            })
-            .count()
+    fn part_two(&self) -> Option<String> {
+        let mut seating = self.seating(Part::Two);
+        seating.run_until_stable(&|position, neighbours| next_position(position, neighbours, 5));
+        Some(number_of_occupied_positions(seating.cells()).to_string())
     }
 
-    fn number_of_occupied_positions(&self) -> usize {
-        self.map
-            .iter()
-            .flatten()
-            .filter(|position| **position == PositionType::OCCUPIED)
-            .count()
-    }
-
-    fn run_iteration(
-        &self,
-        new_position_function: fn(&Self, usize, usize, PositionType) -> PositionType,
-    ) -> Self {
-        Grid {
-            map: self
-                .map
-                .clone()
-                .iter()
-                .enumerate()
-                .map(|(row_idx, row)| {
-                    row.iter()
-                        .enumerate()
-                        .map(|(col_idx, element)| {
-                            new_position_function(self, row_idx, col_idx, *element)
-                        })
-                        .collect()
-                })
-                .collect(),
-        }
-    }
+    fn animate(&self, part: Part) -> Option<Recorder> {
+        let tolerance = match part {
+            Part::One => 4,
+            Part::Two => 5,
+        };
+        let palette = Palette::new(
+            &[
+                ('L', [90, 160, 90]),
+                ('#', [220, 80, 60]),
+                ('.', [40, 40, 40]),
+            ],
+            4,
+        );
+        let mut recorder = Recorder::new(palette);
+        let mut seating = self.seating(part);
 
-    fn run_until_stable(
-        &self,
-        new_position_function: fn(&Self, usize, usize, PositionType) -> PositionType,
-    ) -> Self {
-        let mut current_iteration = self.clone();
-        loop {
-            let next_iteration = current_iteration.run_iteration(new_position_function);
-            if current_iteration == next_iteration {
-                break next_iteration;
-            }
-            current_iteration = next_iteration;
+        recorder.record(seating.to_grid().map(|position| position.to_char()));
+        while seating.step(&|position, neighbours| next_position(position, neighbours, tolerance)) {
+            recorder.record(seating.to_grid().map(|position| position.to_char()));
         }
+        Some(recorder)
     }
+}
 
-    fn get_next_position(
-        grid: &Self,
-        row_idx: usize,
-        col_idx: usize,
-        current_position: PositionType,
-    ) -> PositionType {
-        match (
-            current_position,
-            grid.adjacent_occupied_seats(&row_idx, &col_idx),
-        ) {
-            (PositionType::EMPTY, 0) => PositionType::OCCUPIED,
-            (PositionType::OCCUPIED, occupied) if occupied >= 4 => PositionType::EMPTY,
-            _ => current_position,
-        }
+impl Day11 {
+    /// Part one looks at the adjacent seats, and part two at the first seat in each direction.
+    fn seating(&self, part: Part) -> DenseAutomaton<PositionType> {
+        let neighbourhood = match part {
+            Part::One => Neighbourhood::Adjacent,
+            Part::Two => Neighbourhood::LineOfSight(PositionType::is_floor),
+        };
+        DenseAutomaton::new(&self.initial_layout, &neighbourhood)
     }
+}
 
-    fn get_next_position_advanced(
-        grid: &Self,
-        row_idx: usize,
-        col_idx: usize,
-        current_position: PositionType,
-    ) -> PositionType {
-        match (
-            current_position,
-            grid.viewed_occupied_seats(&row_idx, &col_idx),
-        ) {
-            (PositionType::EMPTY, 0) => PositionType::OCCUPIED,
-            (PositionType::OCCUPIED, occupied) if occupied >= 5 => PositionType::EMPTY,
-            _ => current_position,
-        }
-    }
+fn parse_layout(input: &str) -> Result<Grid<PositionType>> {
+    Grid::parse(
+        input,
+        |c| PositionType::from_str(&c),
+        "one of 'L', '#' or '.'",
+    )
+}
 
-    fn from_str(grid_str: &str) -> Self {
-        let map = grid_str
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .filter_map(|c| PositionType::from_str(&c))
-                    .collect()
-            })
-            .collect();
-        Grid { map }
+/// People take an empty seat when no neighbour is occupied, and leave once `tolerance` are.
+fn next_position(
+    current_position: &PositionType,
+    neighbours: Neighbours<PositionType>,
+    tolerance: usize,
+) -> PositionType {
+    match (current_position, number_of_occupied_positions(neighbours)) {
+        (PositionType::Empty, 0) => PositionType::Occupied,
+        (PositionType::Occupied, occupied) if occupied >= tolerance => PositionType::Empty,
+        _ => *current_position,
     }
 }
-impl std::fmt::Display for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let grid = self
-            .map
-            .iter()
-            .map(|line| {
-                line.iter()
-                    .map(|position| position.to_char())
-                    .collect::<String>()
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        write!(f, "{}", grid)
-    }
+
+fn number_of_occupied_positions<'a, I: Iterator<Item = &'a PositionType>>(positions: I) -> usize {
+    positions
+        .filter(|position| **position == PositionType::Occupied)
+        .count()
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 enum PositionType {
-    EMPTY,
-    OCCUPIED,
-    FLOOR,
+    Empty,
+    Occupied,
+    Floor,
 }
 
 impl PositionType {
     fn from_str(position_str: &char) -> Option<PositionType> {
         match position_str {
-            'L' => Some(PositionType::EMPTY),
-            '#' => Some(PositionType::OCCUPIED),
-            '.' => Some(PositionType::FLOOR),
+            'L' => Some(PositionType::Empty),
+            '#' => Some(PositionType::Occupied),
+            '.' => Some(PositionType::Floor),
             _ => None,
         }
     }
 
-    fn to_char(&self) -> char {
+    fn is_floor(&self) -> bool {
+        *self == PositionType::Floor
+    }
+
+    fn to_char(self) -> char {
         match self {
-            PositionType::EMPTY => 'L',
-            PositionType::OCCUPIED => '#',
-            PositionType::FLOOR => '.',
+            PositionType::Empty => 'L',
+            PositionType::Occupied => '#',
+            PositionType::Floor => '.',
         }
     }
 }
 
+impl std::fmt::Display for PositionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,19 +137,19 @@ mod tests {
 ##.";
     const SMALL_MAP: [[PositionType; 3]; 3] = [
         [
-            PositionType::OCCUPIED,
-            PositionType::EMPTY,
-            PositionType::OCCUPIED,
+            PositionType::Occupied,
+            PositionType::Empty,
+            PositionType::Occupied,
         ],
         [
-            PositionType::FLOOR,
-            PositionType::OCCUPIED,
-            PositionType::FLOOR,
+            PositionType::Floor,
+            PositionType::Occupied,
+            PositionType::Floor,
         ],
         [
-            PositionType::OCCUPIED,
-            PositionType::OCCUPIED,
-            PositionType::FLOOR,
+            PositionType::Occupied,
+            PositionType::Occupied,
+            PositionType::Floor,
         ],
     ];
 
@@ -290,24 +164,51 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
+    fn small_layout() -> Grid<PositionType> {
+        Grid::new(3, 3, SMALL_MAP.iter().flatten().copied().collect())
+    }
+
+    fn stable_layout(
+        layout: &str,
+        neighbourhood: Neighbourhood<PositionType>,
+        tolerance: usize,
+    ) -> (Grid<PositionType>, usize) {
+        let mut seating = DenseAutomaton::new(&parse_layout(layout).unwrap(), &neighbourhood);
+        let iterations = seating.run_until_stable(&|position, neighbours| {
+            next_position(position, neighbours, tolerance)
+        });
+        (seating.to_grid(), iterations)
+    }
+
+    #[test]
+    fn test_animate() {
+        let day = Day11::parse(TEST_CASE_1).unwrap();
+        let recorder = day.animate(Part::One).unwrap();
+
+        assert_eq!(6, recorder.frames().len());
+        assert_eq!(TEST_CASE_1, recorder.frames()[0].to_string());
+        assert_eq!(
+            37,
+            recorder.frames()[5].cells().filter(|c| **c == '#').count()
+        );
+    }
+
     #[test]
     fn test_position_type_from_str() {
-        assert_eq!(Some(PositionType::EMPTY), PositionType::from_str(&'L'));
-        assert_eq!(Some(PositionType::FLOOR), PositionType::from_str(&'.'));
-        assert_eq!(Some(PositionType::OCCUPIED), PositionType::from_str(&'#'));
+        assert_eq!(Some(PositionType::Empty), PositionType::from_str(&'L'));
+        assert_eq!(Some(PositionType::Floor), PositionType::from_str(&'.'));
+        assert_eq!(Some(PositionType::Occupied), PositionType::from_str(&'#'));
     }
 
     #[test]
     fn test_grid_get() {
-        let grid = Grid {
-            map: SMALL_MAP.to_vec().iter().map(|row| row.to_vec()).collect(),
-        };
-        assert_eq!(Some(&PositionType::EMPTY), grid.get(&0, &1));
-        assert_eq!(Some(&PositionType::OCCUPIED), grid.get(&1, &1));
-        assert_eq!(Some(&PositionType::FLOOR), grid.get(&2, &2));
-        assert_eq!(None, grid.get(&1, &3));
-        assert_eq!(None, grid.get(&3, &1));
-        assert_eq!(None, grid.get(&3, &3));
+        let grid = small_layout();
+        assert_eq!(Some(&PositionType::Empty), grid.get(0, 1));
+        assert_eq!(Some(&PositionType::Occupied), grid.get(1, 1));
+        assert_eq!(Some(&PositionType::Floor), grid.get(2, 2));
+        assert_eq!(None, grid.get(1, 3));
+        assert_eq!(None, grid.get(3, 1));
+        assert_eq!(None, grid.get(3, 3));
     }
 
     #[test]
@@ -315,20 +216,18 @@ L.LLLLL.LL";
         let map = ".............
 .L.L.#.#.#.#.
 .............";
-        let grid = Grid::from_str(&map);
+        let grid = parse_layout(map).unwrap();
         assert_eq!(13, grid.width());
         assert_eq!(3, grid.height());
     }
 
     #[test]
     fn test_number_of_adjacent_occupied_seats() {
-        let grid = Grid {
-            map: SMALL_MAP.to_vec().iter().map(|row| row.to_vec()).collect(),
-        };
+        let seating = DenseAutomaton::new(&small_layout(), &Neighbourhood::Adjacent);
 
-        assert_eq!(1, grid.adjacent_occupied_seats(&0, &0));
-        assert_eq!(4, grid.adjacent_occupied_seats(&1, &1));
-        assert_eq!(2, grid.adjacent_occupied_seats(&2, &1));
+        assert_eq!(1, number_of_occupied_positions(seating.neighbours(0, 0)));
+        assert_eq!(4, number_of_occupied_positions(seating.neighbours(1, 1)));
+        assert_eq!(2, number_of_occupied_positions(seating.neighbours(2, 1)));
     }
 
     #[test]
@@ -364,49 +263,50 @@ LLLLLLLLL#
 #.LLLLLL.L
 #.LLLLL.L#";
 
-        assert_eq!(8, Grid::from_str(&map_1).viewed_occupied_seats(&4, &3));
-        assert_eq!(0, Grid::from_str(&map_2).viewed_occupied_seats(&3, &3));
-        assert_eq!(0, Grid::from_str(&map_3).viewed_occupied_seats(&1, &1));
-        assert_eq!(1, Grid::from_str(&map_3).viewed_occupied_seats(&1, &3));
-        assert_eq!(0, Grid::from_str(&map_4).viewed_occupied_seats(&0, &3));
+        let viewed_occupied_seats = |map, row, col| {
+            let seating = DenseAutomaton::new(
+                &parse_layout(map).unwrap(),
+                &Neighbourhood::LineOfSight(PositionType::is_floor),
+            );
+            number_of_occupied_positions(seating.neighbours(row, col))
+        };
+
+        assert_eq!(8, viewed_occupied_seats(map_1, 4, 3));
+        assert_eq!(0, viewed_occupied_seats(map_2, 3, 3));
+        assert_eq!(0, viewed_occupied_seats(map_3, 1, 1));
+        assert_eq!(1, viewed_occupied_seats(map_3, 1, 3));
+        assert_eq!(0, viewed_occupied_seats(map_4, 0, 3));
     }
 
     #[test]
     fn test_grid_from_str() {
-        let grid = Grid {
-            map: SMALL_MAP.to_vec().iter().map(|row| row.to_vec()).collect(),
-        };
-        let expected_grid = Grid::from_str(&SMALL_MAP_STR);
+        let grid = small_layout();
+        let expected_grid = parse_layout(SMALL_MAP_STR).unwrap();
 
         assert_eq!(expected_grid, grid);
     }
 
     #[test]
     fn test_run_iteration() {
-        let grid = Grid {
-            map: SMALL_MAP.to_vec().iter().map(|row| row.to_vec()).collect(),
-        };
-        let expected_grid = Grid {
-            map: vec![
-                vec![
-                    PositionType::OCCUPIED,
-                    PositionType::EMPTY,
-                    PositionType::OCCUPIED,
-                ],
-                vec![
-                    PositionType::FLOOR,
-                    PositionType::EMPTY,
-                    PositionType::FLOOR,
-                ],
-                vec![
-                    PositionType::OCCUPIED,
-                    PositionType::OCCUPIED,
-                    PositionType::FLOOR,
-                ],
+        let mut seating = DenseAutomaton::new(&small_layout(), &Neighbourhood::Adjacent);
+        let expected_grid = Grid::new(
+            3,
+            3,
+            vec![
+                PositionType::Occupied,
+                PositionType::Empty,
+                PositionType::Occupied,
+                PositionType::Floor,
+                PositionType::Empty,
+                PositionType::Floor,
+                PositionType::Occupied,
+                PositionType::Occupied,
+                PositionType::Floor,
             ],
-        };
+        );
 
-        assert_eq!(expected_grid, grid.run_iteration(Grid::get_next_position));
+        assert!(seating.step(&|position, neighbours| next_position(position, neighbours, 4)));
+        assert_eq!(expected_grid, seating.to_grid());
     }
 
     #[test]
@@ -421,10 +321,10 @@ L.#.L..#..
 #L#L##L#L#
 #.LLLLLL.L
 #.#L#L#.##";
-        let expected_grid = Grid::from_str(expected_map);
-        let grid = Grid::from_str(&TEST_CASE_1);
-        let grid = grid.run_until_stable(Grid::get_next_position);
+        let expected_grid = parse_layout(expected_map).unwrap();
+        let (grid, iterations) = stable_layout(TEST_CASE_1, Neighbourhood::Adjacent, 4);
         assert_eq!(expected_grid, grid);
+        assert_eq!(5, iterations);
     }
 
     #[test]
@@ -439,10 +339,14 @@ L.L#.LL.L#
 LLL###LLL#
 #.LLLLL#.L
 #.L#LL#.L#";
-        let expected_grid = Grid::from_str(expected_map);
-        let grid = Grid::from_str(&TEST_CASE_1);
-        let grid = grid.run_until_stable(Grid::get_next_position_advanced);
+        let expected_grid = parse_layout(expected_map).unwrap();
+        let (grid, iterations) = stable_layout(
+            TEST_CASE_1,
+            Neighbourhood::LineOfSight(PositionType::is_floor),
+            5,
+        );
         assert_eq!(expected_grid, grid);
+        assert_eq!(6, iterations);
     }
 
     #[test]
@@ -457,7 +361,7 @@ L.#.L..#..
 #L#L##L#L#
 #.LLLLLL.L
 #.#L#L#.##";
-        let expected_grid = Grid::from_str(expected_map);
-        assert_eq!(37, expected_grid.number_of_occupied_positions())
+        let expected_grid = parse_layout(expected_map).unwrap();
+        assert_eq!(37, number_of_occupied_positions(expected_grid.cells()))
     }
 }
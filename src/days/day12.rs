@@ -12,29 +12,62 @@ const WAYPOINT_START_POSITION: Position = Position {
     angle: 90,
 };
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day12.txt";
-    let inputs = read_inputs(&filename);
-    let inputs: Vec<Move> = inputs
-        .lines()
-        .filter_map(|line| Move::from_str(line))
-        .collect();
-
-    let part_one_final_position = run_all_moves(&START_POSITION, &inputs);
-    let part_one = Some(
-        START_POSITION
-            .manhattan_distance(&part_one_final_position)
-            .to_string(),
-    );
-    let part_two_final_position =
-        run_all_moves_relative_to_waypoint(&START_POSITION, &WAYPOINT_START_POSITION, &inputs);
-    let part_two = Some(
-        START_POSITION
-            .manhattan_distance(&part_two_final_position)
-            .to_string(),
-    );
-
-    (part_one, part_two)
+pub struct Day12 {
+    moves: Vec<Move>,
+}
+
+impl Solution for Day12 {
+    fn parse(input: &str) -> Result<Self> {
+        let moves = input
+            .lines()
+            .map(|line| match Move::from_str(line) {
+                Some(Move {
+                    move_type: MoveType::Left,
+                    units,
+                })
+                | Some(Move {
+                    move_type: MoveType::Right,
+                    units,
+                }) if units % 90 != 0 => Err(Error::at(
+                    input,
+                    &line[1..],
+                    format!("expected a multiple of 90 degrees, found {}", units),
+                )),
+                Some(move_desc) => Ok(move_desc),
+                None => Err(Error::at(
+                    input,
+                    line,
+                    format!(
+                        "expected an action (N, S, E, W, L, R or F) and a number, found '{}'",
+                        line
+                    ),
+                )),
+            })
+            .collect::<Result<Vec<Move>>>()?;
+        Ok(Day12 { moves })
+    }
+
+    fn part_one(&self) -> Option<String> {
+        let final_position = run_all_moves(&START_POSITION, &self.moves);
+        Some(
+            START_POSITION
+                .manhattan_distance(&final_position)
+                .to_string(),
+        )
+    }
+
+    fn part_two(&self) -> Option<String> {
+        let final_position = run_all_moves_relative_to_waypoint(
+            &START_POSITION,
+            &WAYPOINT_START_POSITION,
+            &self.moves,
+        );
+        Some(
+            START_POSITION
+                .manhattan_distance(&final_position)
+                .to_string(),
+        )
+    }
 }
 
 fn run_all_moves(starting_position: &Position, moves: &[Move]) -> Position {
@@ -60,15 +93,14 @@ fn run_all_moves_relative_to_waypoint(
 
                 match current_move.move_type {
                     MoveType::North | MoveType::South | MoveType::East | MoveType::West => {
-                        new_waypoint = current_waypoint_position.move_position(&current_move);
+                        new_waypoint = current_waypoint_position.move_position(current_move);
                     }
                     MoveType::Right | MoveType::Left => {
-                        new_waypoint =
-                            current_waypoint_position.rotate_around_origin(&current_move);
+                        new_waypoint = current_waypoint_position.rotate_around_origin(current_move);
                     }
                     MoveType::Forward => {
                         new_position = current_position
-                            .move_to_waypoint(&current_move, &current_waypoint_position);
+                            .move_to_waypoint(current_move, &current_waypoint_position);
                     }
                 };
                 (new_position, new_waypoint)
@@ -88,8 +120,7 @@ impl Move {
         let move_type = MoveType::from_str(move_str);
         let units = move_str
             .get(1..)
-            .map(|units_str| units_str.parse::<usize>().ok())
-            .flatten();
+            .and_then(|units_str| units_str.parse::<usize>().ok());
 
         match (move_type, units) {
             (Some(move_type), Some(units)) => Some(Move { move_type, units }),
@@ -247,6 +278,26 @@ F7
 R90
 F11";
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                2,
+                "expected a multiple of 90 degrees, found 45"
+            )),
+            Day12::parse("F10\nR45").err()
+        );
+        assert_eq!(
+            Some(Error::parse(
+                3,
+                1,
+                "expected an action (N, S, E, W, L, R or F) and a number, found 'X7'"
+            )),
+            Day12::parse("F10\nN3\nX7").err()
+        );
+    }
+
     #[test]
     fn test_move_from_str() {
         assert_eq!(
@@ -454,20 +505,14 @@ F11";
 
     #[test]
     fn test_run_all_moves() {
-        let moves: Vec<Move> = TEST_CASE_1
-            .lines()
-            .filter_map(|x| Move::from_str(x))
-            .collect();
+        let moves: Vec<Move> = TEST_CASE_1.lines().filter_map(Move::from_str).collect();
         let final_position = run_all_moves(&START_POSITION, &moves);
         assert_eq!(25, START_POSITION.manhattan_distance(&final_position));
     }
 
     #[test]
     fn test_run_all_moves_relative_to_waypoint() {
-        let moves: Vec<Move> = TEST_CASE_1
-            .lines()
-            .filter_map(|x| Move::from_str(x))
-            .collect();
+        let moves: Vec<Move> = TEST_CASE_1.lines().filter_map(Move::from_str).collect();
         let final_position =
             run_all_moves_relative_to_waypoint(&START_POSITION, &WAYPOINT_START_POSITION, &moves);
         assert_eq!(286, START_POSITION.manhattan_distance(&final_position));
@@ -1,36 +1,58 @@
 use super::lib::*;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day13.txt";
-    let inputs = read_inputs(&filename);
-    let mut inputs = inputs.lines();
-    let offset = inputs
-        .next()
-        .expect("Expected an offset")
-        .parse::<usize>()
-        .expect("Expect offset to be usize");
-    let buses: Vec<(usize, usize)> = inputs
-        .next()
-        .expect("Expected to have buses")
-        .split(',')
-        .enumerate()
-        .filter(|x| x.1.parse::<usize>().is_ok())
-        .map(|x| (x.0, x.1.parse::<usize>().unwrap()))
-        .collect();
+pub struct Day13 {
+    offset: usize,
+    buses: Vec<(usize, usize)>,
+}
 
-    let bus_numbers: Vec<usize> = buses.iter().map(|x| x.1).collect();
+impl Solution for Day13 {
+    fn parse(input: &str) -> Result<Self> {
+        let mut lines = input.lines();
+        let offset = match lines.next() {
+            Some(offset) => parse_token(input, offset.trim(), "a timestamp")?,
+            None => return Err(Error::input("expected a timestamp")),
+        };
+        let buses = match lines.next() {
+            Some(buses) => parse_buses(input, buses)?,
+            None => return Err(Error::input("expected a list of buses")),
+        };
+        Ok(Day13 { offset, buses })
+    }
 
-    let (first_bus_id, waiting_minutes) = find_first_bus(&offset, &bus_numbers);
-    let part_one = Some((first_bus_id * waiting_minutes).to_string());
+    fn part_one(&self) -> Option<String> {
+        let bus_numbers: Vec<usize> = self.buses.iter().map(|x| x.1).collect();
+        let (first_bus_id, waiting_minutes) = find_first_bus(&self.offset, &bus_numbers);
+        Some((first_bus_id * waiting_minutes).to_string())
+    }
 
-    let chinese_remainder_theorem_inputs: Vec<(isize, isize)> = buses
-        .iter()
-        .map(|bus| (-(bus.0 as isize), bus.1 as isize))
-        .collect();
-    let (min_timestamp, _) = chinese_remainder_theorem(&chinese_remainder_theorem_inputs);
-    let part_two = Some(min_timestamp.to_string());
+    fn part_two(&self) -> Option<String> {
+        let chinese_remainder_theorem_inputs: Vec<(isize, isize)> = self
+            .buses
+            .iter()
+            .map(|bus| (-(bus.0 as isize), bus.1 as isize))
+            .collect();
+        let (min_timestamp, _) = chinese_remainder_theorem(&chinese_remainder_theorem_inputs);
+        Some(min_timestamp.to_string())
+    }
+}
 
-    (part_one, part_two)
+fn parse_buses(input: &str, buses: &str) -> Result<Vec<(usize, usize)>> {
+    let buses: Vec<(usize, usize)> = buses
+        .split(',')
+        .enumerate()
+        .filter(|(_, bus)| *bus != "x")
+        .map(
+            |(idx, bus)| match parse_token(input, bus, "a bus number or 'x'")? {
+                0 => Err(Error::at(input, bus, "expected a bus number above 0")),
+                bus => Ok((idx, bus)),
+            },
+        )
+        .collect::<Result<_>>()?;
+
+    if buses.is_empty() {
+        return Err(Error::input("expected at least one bus"));
+    }
+    Ok(buses)
 }
 
 fn find_first_bus(offset: &usize, buses: &[usize]) -> (usize, usize) {
@@ -90,6 +112,37 @@ fn chinese_remainder_theorem(inputs: &[(isize, isize)]) -> (isize, isize) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse() {
+        let day = Day13::parse("939\n7,13,x,x,59").unwrap();
+        assert_eq!(939, day.offset);
+        assert_eq!(vec![(0, 7), (1, 13), (4, 59)], day.buses);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                6,
+                "expected a bus number or 'x', found 'y'"
+            )),
+            Day13::parse("939\n7,13,y,x,59").err()
+        );
+        assert_eq!(
+            Some(Error::parse(1, 1, "expected a timestamp, found 'abc'")),
+            Day13::parse("abc\n7,13").err()
+        );
+        assert_eq!(
+            Some(Error::input("expected a list of buses")),
+            Day13::parse("939").err()
+        );
+        assert_eq!(
+            Some(Error::input("expected at least one bus")),
+            Day13::parse("939\nx,x").err()
+        );
+    }
+
     #[test]
     fn test_find_first_bus() {
         let (bus_id, waiting_minutes) = find_first_bus(&939, &[7, 13, 59, 31, 19]);
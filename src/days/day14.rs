@@ -5,21 +5,87 @@ use self::regex::Regex;
 
 use std::collections::HashMap;
 
+const MASK_LENGTH: usize = 36;
+
 lazy_static! {
-    static ref RE_MASK: Regex = Regex::new(r"mask = (?P<mask>[0|1|X]+)").unwrap();
-    static ref RE_MEMORY: Regex = Regex::new(r"mem\[(?P<address>\d+)\] = (?P<value>\d+)").unwrap();
+    static ref RE_MASK: Regex = Regex::new(r"^mask = (?P<mask>[01X]+)$").unwrap();
+    static ref RE_MEMORY: Regex =
+        Regex::new(r"^mem\[(?P<address>\d+)\] = (?P<value>\d+)$").unwrap();
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Instruction {
+    /// A mask of `MASK_LENGTH` '0', '1' and 'X' bits.
+    Mask(String),
+    Write {
+        address: usize,
+        value: usize,
+    },
+}
+
+pub struct Day14 {
+    program: Vec<Instruction>,
 }
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day14.txt";
-    let inputs = read_inputs(&filename);
+impl Solution for Day14 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Day14 {
+            program: parse_program(input)?,
+        })
+    }
+
+    fn part_one(&self) -> Option<String> {
+        let memory = apply_mask_to_inputs(&self.program);
+        Some(memory.values().sum::<usize>().to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        let memory = apply_memory_address_decoder(&self.program);
+        Some(memory.values().sum::<usize>().to_string())
+    }
+}
 
-    let part_one_memory = apply_mask_to_inputs(&inputs);
-    let part_one = Some(part_one_memory.values().sum::<usize>().to_string());
-    let part_two_memory = apply_memory_address_decoder(&inputs);
-    let part_two = Some(part_two_memory.values().sum::<usize>().to_string());
+fn parse_program(input: &str) -> Result<Vec<Instruction>> {
+    let mut has_mask = false;
 
-    (part_one, part_two)
+    input
+        .lines()
+        .map(|line| {
+            if let Some(mask) = parse_mask(line) {
+                if mask.len() != MASK_LENGTH {
+                    return Err(Error::at(
+                        input,
+                        mask,
+                        format!(
+                            "expected a mask of {} bits, found {}",
+                            MASK_LENGTH,
+                            mask.len()
+                        ),
+                    ));
+                }
+                has_mask = true;
+                Ok(Instruction::Mask(mask.to_string()))
+            } else if let Some((address, value)) = parse_memory(line) {
+                if !has_mask {
+                    return Err(Error::at(
+                        input,
+                        line,
+                        "expected a mask before writing to memory",
+                    ));
+                }
+                Ok(Instruction::Write { address, value })
+            } else {
+                Err(Error::at(
+                    input,
+                    line,
+                    format!(
+                        "expected 'mask = ...' or 'mem[...] = ...', found '{}'",
+                        line
+                    ),
+                ))
+            }
+        })
+        .collect()
 }
 
 fn parse_mask(input: &str) -> Option<&str> {
@@ -32,12 +98,10 @@ fn parse_memory(input: &str) -> Option<(usize, usize)> {
     RE_MEMORY.captures(input).and_then(|cap| {
         let address = cap
             .name("address")
-            .map(|x| x.as_str().parse::<usize>().ok())
-            .flatten();
+            .and_then(|x| x.as_str().parse::<usize>().ok());
         let value = cap
             .name("value")
-            .map(|x| x.as_str().parse::<usize>().ok())
-            .flatten();
+            .and_then(|x| x.as_str().parse::<usize>().ok());
         match (address, value) {
             (Some(address), Some(value)) => Some((address, value)),
             _ => None,
@@ -45,42 +109,42 @@ fn parse_memory(input: &str) -> Option<(usize, usize)> {
     })
 }
 
-fn apply_mask_to_inputs(input: &str) -> HashMap<usize, usize> {
+fn apply_mask_to_inputs(program: &[Instruction]) -> HashMap<usize, usize> {
     let mut mask = "";
     let mut memory = HashMap::new();
 
-    for line in input.lines() {
-        if let Some(new_mask) = parse_mask(line) {
-            mask = new_mask;
-        } else if let Some((address, value)) = parse_memory(line) {
-            let masked_value = apply_mask(&value, mask);
-            memory.insert(address, masked_value);
+    for instruction in program {
+        match instruction {
+            Instruction::Mask(new_mask) => mask = new_mask,
+            Instruction::Write { address, value } => {
+                memory.insert(*address, apply_mask(value, mask));
+            }
         }
     }
     memory
 }
 
-fn apply_memory_address_decoder(input: &str) -> HashMap<usize, usize> {
+fn apply_memory_address_decoder(program: &[Instruction]) -> HashMap<usize, usize> {
     let mut mask = "";
     let mut memory = HashMap::new();
 
-    for line in input.lines() {
-        if let Some(new_mask) = parse_mask(line) {
-            mask = new_mask;
-        } else if let Some((address, value)) = parse_memory(line) {
-            get_all_possible_addresses(&address, mask)
-                .iter()
-                .for_each(|address| {
-                    memory.insert(*address, value);
-                })
+    for instruction in program {
+        match instruction {
+            Instruction::Mask(new_mask) => mask = new_mask,
+            Instruction::Write { address, value } => {
+                for address in get_all_possible_addresses(address, mask) {
+                    memory.insert(address, *value);
+                }
+            }
         }
     }
     memory
 }
 fn apply_mask(input: &usize, mask: &str) -> usize {
     let and_mask =
-        usize::from_str_radix(&mask.replace("X", "1"), 2).expect("Expected a valid mask");
-    let or_mask = usize::from_str_radix(&mask.replace("X", "0"), 2).expect("Expected a valid mask");
+        usize::from_str_radix(&mask.replace("X", "1"), 2).expect("masks are checked when parsing");
+    let or_mask =
+        usize::from_str_radix(&mask.replace("X", "0"), 2).expect("masks are checked when parsing");
 
     (input & and_mask) | or_mask
 }
@@ -132,6 +196,60 @@ fn get_all_possible_masks(mask: &str) -> Vec<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_program() {
+        assert_eq!(
+            Ok(vec![
+                Instruction::Mask("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X".to_string()),
+                Instruction::Write {
+                    address: 8,
+                    value: 11
+                }
+            ]),
+            parse_program("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 11")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 8, "expected a mask of 36 bits, found 3")),
+            parse_program("mask = X1X\nmem[8] = 11")
+        );
+        assert_eq!(
+            Err(Error::parse(
+                1,
+                1,
+                "expected a mask before writing to memory"
+            )),
+            parse_program("mem[8] = 11")
+        );
+        assert_eq!(
+            Err(Error::parse(
+                2,
+                1,
+                "expected 'mask = ...' or 'mem[...] = ...', found 'mem[8] 11'"
+            )),
+            parse_program("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] 11")
+        );
+
+        for line in &[
+            "xmask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X",
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXX|0X",
+            "mem[8] = 11 junk",
+            "mem[7] = 101garbage",
+        ] {
+            let program = format!("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\n{}", line);
+            assert_eq!(
+                Err(Error::parse(
+                    2,
+                    1,
+                    format!(
+                        "expected 'mask = ...' or 'mem[...] = ...', found '{}'",
+                        line
+                    )
+                )),
+                parse_program(&program)
+            );
+        }
+    }
+
     #[test]
     fn test_parse_mask() {
         assert_eq!(
@@ -187,7 +305,7 @@ mod tests {
 mem[8] = 11
 mem[7] = 101
 mem[8] = 0";
-        let memory = apply_mask_to_inputs(input);
+        let memory = apply_mask_to_inputs(&parse_program(input).unwrap());
         assert_eq!(2, memory.len());
         assert_eq!(&101, memory.get(&7).unwrap());
         assert_eq!(&64, memory.get(&8).unwrap());
@@ -199,7 +317,7 @@ mem[8] = 0";
 mem[42] = 100
 mask = 00000000000000000000000000000000X0XX
 mem[26] = 1";
-        let memory = apply_memory_address_decoder(input);
+        let memory = apply_memory_address_decoder(&parse_program(input).unwrap());
         assert_eq!(10, memory.len());
         (58..=59).for_each(|address| assert_eq!(&100, memory.get(&address).unwrap()));
         (16..=19).for_each(|address| assert_eq!(&1, memory.get(&address).unwrap()));
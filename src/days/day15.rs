@@ -2,18 +2,31 @@ use super::lib::*;
 
 use std::collections::HashMap;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day15.txt";
-    let inputs = read_inputs(&filename);
-    let starting_list: Vec<usize> = inputs
-        .split(',')
-        .filter_map(|x| x.parse::<usize>().ok())
-        .collect();
+pub struct Day15 {
+    starting_list: Vec<usize>,
+}
+
+impl Solution for Day15 {
+    fn parse(input: &str) -> Result<Self> {
+        let numbers = input.trim();
+        if numbers.is_empty() {
+            return Err(Error::input("expected a list of starting numbers"));
+        }
+
+        let starting_list = numbers
+            .split(',')
+            .map(|x| parse_token(input, x.trim(), "a starting number"))
+            .collect::<Result<Vec<usize>>>()?;
+        Ok(Day15 { starting_list })
+    }
 
-    let part_one = Some(get_nth_spoken_number(&starting_list, &2020).to_string());
-    let part_two = Some(get_nth_spoken_number(&starting_list, &30000000).to_string());
+    fn part_one(&self) -> Option<String> {
+        Some(get_nth_spoken_number(&self.starting_list, &2020).to_string())
+    }
 
-    (part_one, part_two)
+    fn part_two(&self) -> Option<String> {
+        Some(get_nth_spoken_number(&self.starting_list, &30000000).to_string())
+    }
 }
 
 fn get_nth_spoken_number(starting_list: &[usize], nth_number: &usize) -> usize {
@@ -39,6 +52,22 @@ fn get_nth_spoken_number(starting_list: &[usize], nth_number: &usize) -> usize {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            vec![0, 3, 6],
+            Day15::parse("0,3,6\n").unwrap().starting_list
+        );
+        assert_eq!(
+            Some(Error::parse(1, 3, "expected a starting number, found 'a'")),
+            Day15::parse("0,a,6").err()
+        );
+        assert_eq!(
+            Some(Error::input("expected a list of starting numbers")),
+            Day15::parse("").err()
+        );
+    }
+
     #[test]
     fn test_get_nth_spoken_number() {
         assert_eq!(0, get_nth_spoken_number(&[0, 3, 6], &10));
@@ -10,31 +10,69 @@ use std::collections::HashSet;
 type RuleType = (String, RangeInclusive<usize>, RangeInclusive<usize>);
 type TicketType = Vec<usize>;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day16.txt";
-    let inputs = read_inputs(&filename);
-
-    let rules = parse_rules(&inputs);
-    let nearby_tickets = parse_nearby_tickets(&inputs);
-
-    let invalid_fields = find_invalid_fields(&nearby_tickets, &rules);
-    let part_one = Some(invalid_fields.iter().sum::<usize>().to_string());
-
-    let your_ticket = parse_your_ticket(&inputs).expect("Expected your ticket");
-    let nearby_tickets = filter_valid_tickets(&nearby_tickets, &rules);
-
-    let fields = find_fields(&nearby_tickets, &rules);
-    let part_two = Some(
-        fields
-            .iter()
-            .enumerate()
-            .filter(|(_, field)| field.starts_with("departure"))
-            .filter_map(|(idx, _)| your_ticket.get(idx))
-            .product::<usize>()
-            .to_string(),
-    );
-
-    (part_one, part_two)
+pub struct Day16 {
+    rules: Vec<RuleType>,
+    your_ticket: TicketType,
+    nearby_tickets: Vec<TicketType>,
+}
+
+impl Solution for Day16 {
+    fn parse(input: &str) -> Result<Self> {
+        let day = Day16 {
+            rules: parse_rules(input)?,
+            your_ticket: parse_your_ticket(input)?,
+            nearby_tickets: parse_nearby_tickets(input)?,
+        };
+
+        let tickets = input
+            .split_by_blank_lines()
+            .skip(1)
+            .flat_map(|section| section.lines().skip(1));
+        for ticket in tickets {
+            let values = ticket.split(',').count();
+            if values != day.rules.len() {
+                return Err(Error::at(
+                    input,
+                    ticket,
+                    format!(
+                        "expected {} values, one for each field, found {}",
+                        day.rules.len(),
+                        values
+                    ),
+                ));
+            }
+        }
+        Ok(day)
+    }
+
+    fn part_one(&self) -> Option<String> {
+        let invalid_fields = find_invalid_fields(&self.nearby_tickets, &self.rules);
+        Some(invalid_fields.iter().sum::<usize>().to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        self.try_part_two().ok()?
+    }
+
+    fn try_part_two(&self) -> Result<Option<String>> {
+        let nearby_tickets = filter_valid_tickets(&self.nearby_tickets, &self.rules);
+        if nearby_tickets.is_empty() {
+            return Err(Error::input("none of the nearby tickets is valid"));
+        }
+        let fields = find_fields(&nearby_tickets, &self.rules).ok_or_else(|| {
+            Error::input("the valid nearby tickets don't match each field to a single rule")
+        })?;
+
+        Ok(Some(
+            fields
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| field.starts_with("departure"))
+                .filter_map(|(idx, _)| self.your_ticket.get(idx))
+                .product::<usize>()
+                .to_string(),
+        ))
+    }
 }
 
 fn find_invalid_fields(tickets: &[TicketType], rules: &[RuleType]) -> TicketType {
@@ -43,8 +81,8 @@ fn find_invalid_fields(tickets: &[TicketType], rules: &[RuleType]) -> TicketType
         .map(|ticket| {
             let invalid_fields: Vec<usize> = ticket
                 .iter()
-                .cloned()
                 .filter(|field| !is_field_valid(field, rules))
+                .cloned()
                 .collect();
 
             invalid_fields
@@ -57,8 +95,8 @@ fn find_invalid_fields(tickets: &[TicketType], rules: &[RuleType]) -> TicketType
 fn filter_valid_tickets(tickets: &[TicketType], rules: &[RuleType]) -> Vec<TicketType> {
     tickets
         .iter()
-        .cloned()
         .filter(|ticket| ticket.iter().all(|field| is_field_valid(field, rules)))
+        .cloned()
         .collect()
 }
 
@@ -69,22 +107,34 @@ fn is_field_valid(field: &usize, rules: &[RuleType]) -> bool {
 fn valid_rules_for_field(field: &usize, rules: &[RuleType]) -> HashSet<RuleType> {
     rules
         .iter()
-        .cloned()
         .filter(|rule| rule.1.contains(field) || rule.2.contains(field))
+        .cloned()
         .collect()
 }
 
-fn parse_rules(input: &str) -> Vec<RuleType> {
+fn parse_rules(input: &str) -> Result<Vec<RuleType>> {
     let re = Regex::new(
-        r"(?P<field>[^:]*): (?P<range1_min>\d+)-(?P<range1_max>\d+) or (?P<range2_min>\d+)-(?P<range2_max>\d+)",
+        r"^(?P<field>[^:]*): (?P<range1_min>\d+)-(?P<range1_max>\d+) or (?P<range2_min>\d+)-(?P<range2_max>\d+)$",
     ).unwrap();
     let field_rules = input
         .split_by_blank_lines()
         .next()
-        .expect("Expected field rules section");
-
-    re.captures_iter(field_rules)
-        .filter_map(|capture| {
+        .ok_or_else(|| Error::input("expected a field rules section"))?;
+
+    let mut names = HashSet::new();
+    field_rules
+        .lines()
+        .map(|line| {
+            let capture = re.captures(line).ok_or_else(|| {
+                Error::at(
+                    input,
+                    line,
+                    format!(
+                        "expected '<field>: <min>-<max> or <min>-<max>', found '{}'",
+                        line
+                    ),
+                )
+            })?;
             let field_name = capture.name("field").map(|x| x.as_str().trim().to_string());
             let range1 = create_range(
                 &capture.name("range1_min").map(|x| x.as_str()),
@@ -95,11 +145,23 @@ fn parse_rules(input: &str) -> Vec<RuleType> {
                 &capture.name("range2_max").map(|x| x.as_str()),
             );
 
-            match (field_name, range1, range2) {
-                (Some(field_name), Some(range1), Some(range2)) => {
-                    Some((field_name, range1, range2))
+            if let Some(name) = &field_name {
+                if !names.insert(name.clone()) {
+                    return Err(Error::at(
+                        input,
+                        line,
+                        format!("field '{}' is defined twice", name),
+                    ));
                 }
-                _ => None,
+            }
+
+            match (field_name, range1, range2) {
+                (Some(field_name), Some(range1), Some(range2)) => Ok((field_name, range1, range2)),
+                _ => Err(Error::at(
+                    input,
+                    line,
+                    "expected ranges that fit in a number",
+                )),
             }
         })
         .collect()
@@ -117,41 +179,48 @@ fn create_range(min: &Option<&str>, max: &Option<&str>) -> Option<RangeInclusive
     }
 }
 
-fn parse_your_ticket(input: &str) -> Option<TicketType> {
+fn parse_your_ticket(input: &str) -> Result<TicketType> {
     let your_ticket = input
         .split_by_blank_lines()
         .nth(1)
-        .expect("Expected your ticket section");
-    your_ticket.lines().nth(1).map(parse_ticket)
+        .ok_or_else(|| Error::input("expected a 'your ticket' section"))?;
+    match your_ticket.lines().nth(1) {
+        Some(ticket) => parse_ticket(input, ticket),
+        None => Err(Error::at(input, your_ticket, "expected your ticket")),
+    }
 }
 
-fn parse_nearby_tickets(input: &str) -> Vec<TicketType> {
-    let your_ticket = input
+fn parse_nearby_tickets(input: &str) -> Result<Vec<TicketType>> {
+    let nearby_tickets = input
         .split_by_blank_lines()
         .nth(2)
-        .expect("Expected nearby tickets section");
-    your_ticket.lines().skip(1).map(parse_ticket).collect()
+        .ok_or_else(|| Error::input("expected a 'nearby tickets' section"))?;
+    nearby_tickets
+        .lines()
+        .skip(1)
+        .map(|ticket| parse_ticket(input, ticket))
+        .collect()
 }
 
-fn parse_ticket(input: &str) -> TicketType {
-    input
+fn parse_ticket(input: &str, ticket: &str) -> Result<TicketType> {
+    ticket
         .split(',')
-        .filter_map(|x| x.parse::<usize>().ok())
+        .map(|x| parse_token(input, x, "a ticket field value"))
         .collect()
 }
 
-fn find_fields(tickets: &[TicketType], rules: &[RuleType]) -> Vec<String> {
-    let number_of_fields = tickets.get(0).expect("Expected at least one ticket").len();
+/// The name of each field, in ticket order, or `None` if there are no tickets or the tickets
+/// don't narrow every field down to a single rule.
+fn find_fields(tickets: &[TicketType], rules: &[RuleType]) -> Option<Vec<String>> {
+    let number_of_fields = tickets.first()?.len();
     let mut tickets_transposed: Vec<Vec<usize>> = Vec::with_capacity(number_of_fields);
     tickets_transposed.resize_with(number_of_fields, Vec::new);
 
-    tickets.iter().for_each(|ticket| {
-        ticket
-            .iter()
-            .cloned()
-            .enumerate()
-            .for_each(|(idx, field)| tickets_transposed.get_mut(idx).unwrap().push(field));
-    });
+    for ticket in tickets {
+        for (idx, field) in ticket.iter().enumerate() {
+            tickets_transposed.get_mut(idx)?.push(*field);
+        }
+    }
 
     let valid_rules_for_fields: Vec<Vec<HashSet<RuleType>>> = tickets_transposed
         .iter()
@@ -170,7 +239,7 @@ fn find_fields(tickets: &[TicketType], rules: &[RuleType]) -> Vec<String> {
                 field_column.first().unwrap().clone(),
                 |intersection, field| {
                     intersection
-                        .intersection(&field)
+                        .intersection(field)
                         .cloned()
                         .collect::<HashSet<RuleType>>()
                 },
@@ -198,9 +267,10 @@ fn find_fields(tickets: &[TicketType], rules: &[RuleType]) -> Vec<String> {
 
     valid_rules
         .iter()
-        .map(|(_, rule)| rule)
-        .inspect(|field_rules| assert!(field_rules.len() == 1))
-        .map(|field_rules| field_rules.iter().cloned().next().unwrap().0)
+        .map(|(_, field_rules)| match field_rules.len() {
+            1 => field_rules.iter().next().map(|rule| rule.0.clone()),
+            _ => None,
+        })
         .collect()
 }
 
@@ -241,12 +311,53 @@ nearby tickets:
             ("seat".to_string(), 13..=40, 45..=50),
         ];
 
-        assert_eq!(expected_rules, parse_rules(TEST_CASE_1));
+        assert_eq!(Ok(expected_rules), parse_rules(TEST_CASE_1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                1,
+                "expected '<field>: <min>-<max> or <min>-<max>', found 'row: 6-11'"
+            )),
+            Day16::parse(
+                "class: 1-3 or 5-7\nrow: 6-11\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,3"
+            )
+            .err()
+        );
+        assert_eq!(
+            Some(Error::parse(8, 3, "expected a ticket field value, found 'x'")),
+            Day16::parse("class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,x").err()
+        );
+        assert_eq!(
+            Some(Error::input("expected a 'nearby tickets' section")),
+            Day16::parse("class: 1-3 or 5-7\n\nyour ticket:\n7").err()
+        );
+        assert_eq!(
+            Some(Error::parse(2, 1, "field 'class' is defined twice")),
+            Day16::parse(
+                "class: 1-3 or 5-7\nclass: 1-3 or 5-7\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,1"
+            )
+            .err()
+        );
+        assert_eq!(
+            Some(Error::parse(
+                8,
+                1,
+                "expected 2 values, one for each field, found 3"
+            )),
+            Day16::parse(
+                "class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,3,1"
+            )
+            .err()
+        );
     }
 
     #[test]
     fn test_parse_your_ticket() {
-        assert_eq!(Some(vec![7, 1, 14]), parse_your_ticket(TEST_CASE_1));
+        assert_eq!(Ok(vec![7, 1, 14]), parse_your_ticket(TEST_CASE_1));
     }
 
     #[test]
@@ -257,13 +368,16 @@ nearby tickets:
             vec![55, 2, 20],
             vec![38, 6, 12],
         ];
-        assert_eq!(expected_nearby_tickets, parse_nearby_tickets(TEST_CASE_1));
+        assert_eq!(
+            Ok(expected_nearby_tickets),
+            parse_nearby_tickets(TEST_CASE_1)
+        );
     }
 
     #[test]
     fn test_find_invalid_fields() {
-        let nearby_tickets = parse_nearby_tickets(TEST_CASE_1);
-        let rules = parse_rules(TEST_CASE_1);
+        let nearby_tickets = parse_nearby_tickets(TEST_CASE_1).unwrap();
+        let rules = parse_rules(TEST_CASE_1).unwrap();
 
         assert_eq!(
             vec![4, 55, 12],
@@ -273,8 +387,8 @@ nearby tickets:
 
     #[test]
     fn test_filter_valid_tickets() {
-        let nearby_tickets = parse_nearby_tickets(TEST_CASE_1);
-        let rules = parse_rules(TEST_CASE_1);
+        let nearby_tickets = parse_nearby_tickets(TEST_CASE_1).unwrap();
+        let rules = parse_rules(TEST_CASE_1).unwrap();
         assert_eq!(
             vec![vec![7, 3, 47]],
             filter_valid_tickets(&nearby_tickets, &rules)
@@ -284,9 +398,34 @@ nearby tickets:
     #[test]
     fn test_find_field_candidates() {
         let expected_fields = vec!["row".to_string(), "class".to_string(), "seat".to_string()];
-        let nearby_tickets = parse_nearby_tickets(TEST_CASE_2);
-        let rules = parse_rules(TEST_CASE_2);
+        let nearby_tickets = parse_nearby_tickets(TEST_CASE_2).unwrap();
+        let rules = parse_rules(TEST_CASE_2).unwrap();
+
+        assert_eq!(Some(expected_fields), find_fields(&nearby_tickets, &rules));
+        assert_eq!(None, find_fields(&[], &rules));
+    }
 
-        assert_eq!(expected_fields, find_fields(&nearby_tickets, &rules));
+    #[test]
+    fn test_part_two_errors() {
+        let day = Day16::parse(
+            "class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,50\n90,1",
+        )
+        .unwrap();
+        assert_eq!(
+            Err(Error::input("none of the nearby tickets is valid")),
+            day.try_part_two()
+        );
+        assert_eq!(None, day.part_two());
+
+        let day = Day16::parse(
+            "class: 1-3 or 5-7\nrow: 1-3 or 5-7\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,1",
+        )
+        .unwrap();
+        assert_eq!(
+            Err(Error::input(
+                "the valid nearby tickets don't match each field to a single rule"
+            )),
+            day.try_part_two()
+        );
     }
 }
@@ -1,184 +1,83 @@
+use super::lib::automaton::{Automaton, Rule};
 use super::lib::*;
 
-use std::collections::HashMap;
-
-extern crate itertools;
-use self::itertools::*;
-
-lazy_static! {
-    static ref DELTA_COORDINATES: HashMap<usize, Vec<Vec<isize>>> = {
-        let mut deltas = HashMap::new();
-        deltas.insert(3, calculate_delta_coordinates(&3));
-        deltas.insert(4, calculate_delta_coordinates(&4));
-        deltas
-    };
-}
-
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day17.txt";
-    let inputs = read_inputs(&filename);
-
-    let grid_3d = run_n_times(&Grid::from_str(&inputs, &3), &6);
-    let part_one = Some(grid_3d.number_of_active_cells().to_string());
-
-    let grid_4d = run_n_times(&Grid::from_str(&inputs, &4), &6);
-    let part_two = Some(grid_4d.number_of_active_cells().to_string());
-
-    (part_one, part_two)
-}
-
-fn run_n_times(grid: &Grid, n: &usize) -> Grid {
-    let mut grid = grid.clone();
-    for _ in 0..*n {
-        grid = grid.next_iteration();
-    }
-
-    grid
+pub struct Day17 {
+    initial_state: Grid<PositionType>,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-struct Grid {
-    map: HashMap<Vec<isize>, PositionType>,
-    dimensions: usize,
-}
-
-impl Grid {
-    pub fn new(map: HashMap<Vec<isize>, PositionType>, dimensions: usize) -> Self {
-        Self { map, dimensions }
-    }
-
-    pub fn from_str(grid_str: &str, dimensions: &usize) -> Self {
-        let map = grid_str
-            .lines()
-            .enumerate()
-            .map(|(row, line)| {
-                line.chars()
-                    .enumerate()
-                    .filter(|(_, c)| PositionType::from_str(&c).is_some())
-                    .map(|(col, c)| {
-                        let mut coordinate = vec![row as isize, col as isize];
-                        coordinate.resize(*dimensions, 0);
+impl Solution for Day17 {
+    fn parse(input: &str) -> Result<Self> {
+        if input.trim().is_empty() {
+            return Err(Error::input("expected an initial state"));
+        }
 
-                        (coordinate, PositionType::from_str(&c).unwrap())
-                    })
-                    .collect::<Vec<(Vec<isize>, PositionType)>>()
-            })
-            .flatten()
-            .filter(|(_, position_type)| position_type == &PositionType::ACTIVE)
-            .collect();
-        Grid::new(map, *dimensions)
+        Ok(Day17 {
+            initial_state: parse_plane(input)?,
+        })
     }
 
-    pub fn get(&self, coordinates: &[isize]) -> Option<&PositionType> {
-        self.map.get(coordinates).or(Some(&PositionType::INACTIVE))
+    fn part_one(&self) -> Option<String> {
+        let mut automaton = pocket_dimension::<3>(&self.initial_state);
+        automaton.run(6);
+        Some(automaton.population().to_string())
     }
 
-    pub fn number_of_active_cells(&self) -> usize {
-        self.map.len()
+    fn part_two(&self) -> Option<String> {
+        let mut automaton = pocket_dimension::<4>(&self.initial_state);
+        automaton.run(6);
+        Some(automaton.population().to_string())
     }
 
-    fn neighbours_coordinates(&self, coordinates: &[isize]) -> Vec<Vec<isize>> {
-        let dimensions = coordinates.len();
-        let deltas = DELTA_COORDINATES.get(&dimensions).unwrap();
-
-        deltas
-            .iter()
-            .map(|delta| add_position_by_position(delta, &coordinates))
-            .collect()
+    fn animate(&self, part: Part) -> Option<Recorder> {
+        Some(match part {
+            Part::One => record_boot_cycles(pocket_dimension::<3>(&self.initial_state)),
+            Part::Two => record_boot_cycles(pocket_dimension::<4>(&self.initial_state)),
+        })
     }
+}
 
-    fn active_neighbours(&self, coordinates: &[isize]) -> usize {
-        self.neighbours_coordinates(coordinates)
-            .iter()
-            .filter_map(|position| self.get(&position))
-            .filter(|x| **x == PositionType::ACTIVE)
-            .count()
-    }
+fn parse_plane(input: &str) -> Result<Grid<PositionType>> {
+    Grid::parse(input, |c| PositionType::from_str(&c), "'#' or '.'")
+}
 
-    pub fn next_iteration(&self) -> Self {
-        let coordinates = self
-            .map
-            .keys()
-            .map(|x| {
-                let mut neighbours = self.neighbours_coordinates(x);
-                neighbours.push(x.clone());
-                neighbours
-            })
-            .flatten();
+/// The initial state placed in a `D` dimensional pocket dimension, which follows Conway's rules.
+fn pocket_dimension<const D: usize>(plane: &Grid<PositionType>) -> Automaton<D> {
+    Automaton::from_plane(
+        plane,
+        |position_type| *position_type == PositionType::Active,
+        Rule::conway(),
+    )
+}
 
-        let map = coordinates
-            .map(|coordinate| {
-                (
-                    coordinate.clone(),
-                    self.next_position_for_coordinate(&coordinate),
-                )
-            })
-            .filter(|(_, position_type)| position_type == &PositionType::ACTIVE)
-            .collect();
+/// The initial state and each of the 6 boot cycles, with the planes laid out side by side.
+fn record_boot_cycles<const D: usize>(mut automaton: Automaton<D>) -> Recorder {
+    let palette = Palette::new(&[('#', [250, 200, 60]), ('.', [30, 30, 60])], 4);
+    let mut recorder = Recorder::new(palette);
 
-        Grid::new(map, self.dimensions)
-    }
-
-    fn next_position_for_coordinate(&self, coordinates: &[isize]) -> PositionType {
-        match (self.get(coordinates), self.active_neighbours(coordinates)) {
-            (Some(PositionType::ACTIVE), x) if x == 2 || x == 3 => PositionType::ACTIVE,
-            (Some(PositionType::INACTIVE), 3) => PositionType::ACTIVE,
-            _ => PositionType::INACTIVE,
-        }
+    recorder.record(automaton.render('#', '.'));
+    for _ in 0..6 {
+        automaton.step();
+        recorder.record(automaton.render('#', '.'));
     }
+    recorder
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 enum PositionType {
-    ACTIVE,
-    INACTIVE,
+    Active,
+    Inactive,
 }
 
 impl PositionType {
     fn from_str(position_str: &char) -> Option<PositionType> {
         match position_str {
-            '#' => Some(PositionType::ACTIVE),
-            '.' => Some(PositionType::INACTIVE),
+            '#' => Some(PositionType::Active),
+            '.' => Some(PositionType::Inactive),
             _ => None,
         }
     }
 }
 
-#[inline]
-fn add_position_by_position(a: &[isize], b: &[isize]) -> Vec<isize> {
-    a.iter()
-        .zip(b.iter())
-        .map(|(left, right)| left + right)
-        .collect()
-}
-
-fn calculate_delta_coordinates(dimension: &usize) -> Vec<Vec<isize>> {
-    let positions = [-1, 0, 1];
-
-    let mut deltas: Vec<Vec<isize>> = positions
-        .iter()
-        .cartesian_product(positions.iter())
-        .map(|(left, right)| vec![*left, *right])
-        .collect();
-
-    for _ in 2..*dimension {
-        deltas = deltas
-            .iter()
-            .cartesian_product(positions.iter())
-            .map(|(left, right)| {
-                let mut left = left.clone();
-                left.push(*right);
-                left
-            })
-            .collect();
-    }
-
-    deltas
-        .iter()
-        .cloned()
-        .filter(|x| x != &vec![0, 0, 0] && x != &vec![0, 0, 0, 0])
-        .collect()
-}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,83 +86,80 @@ mod tests {
 ..#
 ###";
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(2, 2, "expected '#' or '.', found 'x'")),
+            Day17::parse(".#.\n.x#\n###").err()
+        );
+        assert_eq!(
+            Some(Error::input("expected an initial state")),
+            Day17::parse("").err()
+        );
+    }
+
     #[test]
     fn test_position_type_from_str() {
-        assert_eq!(Some(PositionType::ACTIVE), PositionType::from_str(&'#'));
-        assert_eq!(Some(PositionType::INACTIVE), PositionType::from_str(&'.'));
+        assert_eq!(Some(PositionType::Active), PositionType::from_str(&'#'));
+        assert_eq!(Some(PositionType::Inactive), PositionType::from_str(&'.'));
         assert_eq!(None, PositionType::from_str(&'-'));
     }
 
     #[test]
-    fn test_grid_from_str() {
+    fn test_pocket_dimension() {
         let expected = [
-            [
-                PositionType::INACTIVE,
-                PositionType::ACTIVE,
-                PositionType::INACTIVE,
-            ],
-            [
-                PositionType::INACTIVE,
-                PositionType::INACTIVE,
-                PositionType::ACTIVE,
-            ],
-            [
-                PositionType::ACTIVE,
-                PositionType::ACTIVE,
-                PositionType::ACTIVE,
-            ],
+            [false, true, false],
+            [false, false, true],
+            [true, true, true],
         ];
-        let grid = Grid::from_str(TEST_CASE_1, &3);
+        let automaton = pocket_dimension::<3>(&parse_plane(TEST_CASE_1).unwrap());
 
         for (row_idx, row) in expected.iter().enumerate() {
-            for (col_idx, position) in row.iter().enumerate() {
+            for (col_idx, alive) in row.iter().enumerate() {
                 assert_eq!(
-                    Some(position),
-                    grid.get(&[row_idx as isize, col_idx as isize, 0])
+                    *alive,
+                    automaton.is_alive(&[row_idx as i32, col_idx as i32, 0])
                 );
             }
         }
     }
 
     #[test]
-    fn test_grid_active_neighbours() {
-        let grid = Grid::from_str(TEST_CASE_1, &3);
-        assert_eq!(5, grid.active_neighbours(&[1, 1, 1]));
-        assert_eq!(1, grid.active_neighbours(&[2, 0, 0]));
+    fn test_pocket_dimension_live_neighbours() {
+        let automaton = pocket_dimension::<3>(&parse_plane(TEST_CASE_1).unwrap());
+        assert_eq!(5, automaton.live_neighbours(&[1, 1, 1]));
+        assert_eq!(1, automaton.live_neighbours(&[2, 0, 0]));
     }
 
     #[test]
-    fn test_grid_neighbours_coordinates() {
-        let grid = Grid::from_str(TEST_CASE_1, &3);
-        assert_eq!(26, grid.neighbours_coordinates(&[5, 5, 5]).len());
-        let grid = Grid::from_str(TEST_CASE_1, &4);
-        assert_eq!(80, grid.neighbours_coordinates(&[5, 5, 5, 5]).len());
+    fn test_pocket_dimension_next_state() {
+        let automaton = pocket_dimension::<3>(&parse_plane(TEST_CASE_1).unwrap());
+        assert!(automaton.next_state(&[1, 0, -1]));
+        assert!(!automaton.next_state(&[2, 1, -1]));
+        assert!(!automaton.next_state(&[2, 0, 0]));
     }
 
     #[test]
-    fn test_grid_next_position_for_coordinate() {
-        let grid = Grid::from_str(TEST_CASE_1, &3);
-        assert_eq!(
-            PositionType::ACTIVE,
-            grid.next_position_for_coordinate(&[1, 0, -1])
-        );
-        assert_eq!(
-            PositionType::INACTIVE,
-            grid.next_position_for_coordinate(&[2, 1, -1])
-        );
+    fn test_animate() {
+        let day = Day17::parse(TEST_CASE_1).unwrap();
+        let recorder = day.animate(Part::One).unwrap();
+
+        assert_eq!(7, recorder.frames().len());
+        assert_eq!(TEST_CASE_1, recorder.frames()[0].to_string());
         assert_eq!(
-            PositionType::INACTIVE,
-            grid.next_position_for_coordinate(&[2, 0, 0])
+            "#.. #.# #..\n..# .## ..#\n.#. .#. .#.",
+            recorder.frames()[1].to_string()
         );
     }
 
     #[test]
-    fn test_grid_next_iteration() {
-        let mut grid = Grid::from_str(TEST_CASE_1, &3);
-        for _ in 1..=6 {
-            grid = grid.next_iteration();
-        }
-
-        assert_eq!(112, grid.number_of_active_cells());
+    fn test_pocket_dimension_run() {
+        let mut automaton = pocket_dimension::<3>(&parse_plane(TEST_CASE_1).unwrap());
+        automaton.run(6);
+        assert_eq!(112, automaton.population());
+
+        let mut automaton = pocket_dimension::<4>(&parse_plane(TEST_CASE_1).unwrap());
+        automaton.run(6);
+        assert_eq!(848, automaton.population());
     }
 }
@@ -1,168 +1,99 @@
+use super::lib::expression::{Expression, Precedence};
 use super::lib::*;
-use std::ops::{Add, Mul};
+use std::collections::HashMap;
 
-extern crate regex;
-use self::regex::Regex;
-
-extern crate itertools;
-use self::itertools::*;
-lazy_static! {
-    static ref RE: Regex = Regex::new(r"\((?P<inner_expression>[\d*+ ]+)\)").unwrap();
+pub struct Day18 {
+    homework: Vec<Line>,
 }
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day18.txt";
-    let inputs = read_inputs(&filename);
-
-    let sum_of_results: isize = inputs
-        .lines()
-        .map(eval_expression)
-        .filter_map(|result| result.parse::<isize>().ok())
-        .sum();
-
-    let part_one = Some(sum_of_results.to_string());
-
-    let sum_of_results: isize = inputs
-        .lines()
-        .map(eval_expression_with_add_precedence)
-        .filter_map(|result| result.parse::<isize>().ok())
-        .sum();
-
-    let part_two = Some(sum_of_results.to_string());
-
-    (part_one, part_two)
-}
-
-fn eval_expression(expression: &str) -> String {
-    let find_first_operation_idx: fn(&[Element]) -> usize = |_| 3;
-
-    let expression = simplify_expression(expression, eval_expression);
-    solve_expression(&expression, find_first_operation_idx).to_string()
+/// A line of the homework, parsed once with the precedence of each part.
+struct Line {
+    number: usize,
+    left_to_right: Expression,
+    addition_first: Expression,
 }
 
-fn eval_expression_with_add_precedence(expression: &str) -> String {
-    let expression = simplify_expression(expression, eval_expression_with_add_precedence);
-    solve_expression(&expression, find_next_add_idx).to_string()
-}
-
-fn find_next_add_idx(elements: &[Element]) -> usize {
-    elements
-        .iter()
-        .enumerate()
-        .find(|(_, x)| x.element_type == ElementType::ADD)
-        .map_or(3, |(idx, _)| idx + 2)
-}
-
-fn simplify_expression(expression: &str, eval_function: fn(&str) -> String) -> String {
-    if RE.is_match(expression) {
-        let new_expression = RE
-            .captures_iter(expression)
-            .map(|capture| capture.name("inner_expression").unwrap().as_str())
-            .dedup()
-            .fold(expression.to_string(), |new_expression, capture| {
-                let capture_with_brackets = format!("({})", capture);
-                new_expression.replace(
-                    capture_with_brackets.as_str(),
-                    eval_function(capture).as_str(),
-                )
-            });
-        simplify_expression(&new_expression, eval_function)
-    } else {
-        expression.to_string()
+impl Solution for Day18 {
+    fn parse(input: &str) -> Result<Self> {
+        let homework = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                Ok(Line {
+                    number: idx + 1,
+                    left_to_right: Expression::parse(input, line, &Precedence::left_to_right())?,
+                    addition_first: Expression::parse(input, line, &Precedence::addition_first())?,
+                })
+            })
+            .collect::<Result<Vec<Line>>>()?;
+        Ok(Day18 { homework })
     }
-}
-
-fn solve_expression(expression: &str, next_index: fn(&[Element]) -> usize) -> isize {
-    let mut expr_vec: Vec<Element> = expression
-        .split_whitespace()
-        .filter_map(|x| Element::from_str(x))
-        .collect();
 
-    while expr_vec.len() > 1 {
-        let idx = next_index(&expr_vec);
-        let (left, right) = expr_vec.split_at(idx);
-        let new_element = Element::eval(&left[idx - 3], &left[idx - 2], &left[idx - 1]).unwrap();
-
-        expr_vec = vec![left[0..idx - 3].to_vec(), vec![new_element], right.to_vec()].concat();
+    fn part_one(&self) -> Option<String> {
+        self.try_part_one().ok()?
     }
-    expr_vec[0].value.unwrap()
-}
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct Element {
-    element_type: ElementType,
-    value: Option<isize>,
-}
-
-impl Element {
-    fn new_number(value: isize) -> Self {
-        Element {
-            element_type: ElementType::NUMBER,
-            value: Some(value),
-        }
+    fn part_two(&self) -> Option<String> {
+        self.try_part_two().ok()?
     }
 
-    fn new_add() -> Self {
-        Element {
-            element_type: ElementType::ADD,
-            value: None,
-        }
+    fn try_part_one(&self) -> Result<Option<String>> {
+        sum_of_results(&self.homework, |line| &line.left_to_right).map(|sum| Some(sum.to_string()))
     }
 
-    fn new_mul() -> Self {
-        Element {
-            element_type: ElementType::MULTIPLY,
-            value: None,
-        }
+    fn try_part_two(&self) -> Result<Option<String>> {
+        sum_of_results(&self.homework, |line| &line.addition_first).map(|sum| Some(sum.to_string()))
     }
+}
 
-    fn from_str(input: &str) -> Option<Self> {
-        let input = input.trim();
-        match input {
-            "+" => Some(Self::new_add()),
-            "*" => Some(Self::new_mul()),
-            x => x.parse::<isize>().ok().map(Self::new_number),
-        }
-    }
+/// The sum of the value of every line, as parsed by `expression`. Fails, pointing at the line,
+/// if a value can't be worked out, and also if the sum doesn't fit in an `i64`.
+fn sum_of_results(homework: &[Line], expression: fn(&Line) -> &Expression) -> Result<i64> {
+    let variables = HashMap::new();
+    homework.iter().try_fold(0i64, |sum, line| {
+        let value = expression(line)
+            .eval(&variables)
+            .map_err(|error| Error::line(line.number, error.to_string()))?;
+        sum.checked_add(value)
+            .ok_or_else(|| Error::line(line.number, "the sum of the results overflows"))
+    })
+}
 
-    fn eval(left: &Element, op: &Element, right: &Element) -> Option<Element> {
-        match (left.element_type, op.element_type, right.element_type) {
-            (ElementType::NUMBER, ElementType::ADD, ElementType::NUMBER) => *left + *right,
-            (ElementType::NUMBER, ElementType::MULTIPLY, ElementType::NUMBER) => *left * *right,
-            _ => None,
-        }
-    }
+/// A single expression parsed by `evaluate`, and what came of working it out.
+pub struct Evaluation {
+    pub tree: Expression,
+    /// The value, and each operation in the order it was worked out.
+    pub result: Result<(i64, Vec<String>)>,
 }
 
-impl Add for Element {
-    type Output = Option<Self>;
-    fn add(self, other: Self) -> Option<Self> {
-        match (self.element_type, other.element_type) {
-            (ElementType::NUMBER, ElementType::NUMBER) => Some(Element::new_number(
-                self.value.unwrap() + other.value.unwrap(),
-            )),
-            _ => None,
-        }
+impl Evaluation {
+    pub fn value(self) -> Result<i64> {
+        self.result.map(|(value, _)| value)
     }
 }
 
-impl Mul for Element {
-    type Output = Option<Self>;
-    fn mul(self, other: Self) -> Option<Self> {
-        match (self.element_type, other.element_type) {
-            (ElementType::NUMBER, ElementType::NUMBER) => Some(Element::new_number(
-                self.value.unwrap() * other.value.unwrap(),
-            )),
-            _ => None,
-        }
-    }
+/// Parses `expression` with `precedence` and works it out using `variables`. Only a parse error
+/// fails the whole thing; an expression that parses comes back with its tree even if it can't be
+/// worked out.
+pub fn evaluate(
+    expression: &str,
+    precedence: &Precedence,
+    variables: &HashMap<String, i64>,
+) -> Result<Evaluation> {
+    let tree = Expression::parse(expression, expression, precedence)?;
+    let result = tree.eval_steps(variables);
+    Ok(Evaluation { tree, result })
+}
+
+/// Evaluates `expression` from left to right, with no operator taking precedence.
+pub fn eval_expression(expression: &str) -> Result<i64> {
+    evaluate(expression, &Precedence::left_to_right(), &HashMap::new()).and_then(Evaluation::value)
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum ElementType {
-    NUMBER,
-    ADD,
-    MULTIPLY,
+/// Evaluates `expression` with additions before multiplications.
+pub fn eval_expression_with_add_precedence(expression: &str) -> Result<i64> {
+    evaluate(expression, &Precedence::addition_first(), &HashMap::new()).and_then(Evaluation::value)
 }
 
 #[cfg(test)]
@@ -170,102 +101,128 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_eval_expression() {
-        assert_eq!("71", eval_expression("1 + 2 * 3 + 4 * 5 + 6"));
-        assert_eq!("11", eval_expression("(5 + 6)"));
-        assert_eq!("51", eval_expression("1 + (2 * 3) + (4 * (5 + 6))"));
-        assert_eq!("26", eval_expression("2 * 3 + (4 * 5)"));
-        assert_eq!("437", eval_expression("5 + (8 * 3 + 9 + 3 * 4 * 3)"));
+    fn test_parse_errors() {
         assert_eq!(
-            "12240",
-            eval_expression("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")
+            Some(Error::parse(2, 5, "expected a number or '(', found '*'")),
+            Day18::parse("1 + 2\n3 + * 4").err()
         );
         assert_eq!(
-            "13632",
-            eval_expression("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")
+            Some(Error::parse(1, 8, "expected '+', '*' or ')', found '2'")),
+            Day18::parse("(1 + 2 2)").err()
         );
-    }
-
-    #[test]
-    fn test_eval_expression_with_add_precedence() {
         assert_eq!(
-            "231",
-            eval_expression_with_add_precedence("1 + 2 * 3 + 4 * 5 + 6")
+            Some(Error::parse(1, 1, "unclosed '('")),
+            Day18::parse("(1 + 2").err()
         );
         assert_eq!(
-            "51",
-            eval_expression_with_add_precedence("1 + (2 * 3) + (4 * (5 + 6))")
+            Some(Error::parse(1, 6, "unexpected ')'")),
+            Day18::parse("1 + 2)").err()
         );
-        assert_eq!("46", eval_expression_with_add_precedence("2 * 3 + (4 * 5)"));
         assert_eq!(
-            "1445",
-            eval_expression_with_add_precedence("5 + (8 * 3 + 9 + 3 * 4 * 3)")
+            Some(Error::parse(1, 4, "expected a number")),
+            Day18::parse("1 +").err()
         );
+        assert!(Day18::parse("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2").is_ok());
+    }
+
+    #[test]
+    fn test_eval_expression() {
+        assert_eq!(Ok(71), eval_expression("1 + 2 * 3 + 4 * 5 + 6"));
+        assert_eq!(Ok(11), eval_expression("(5 + 6)"));
+        assert_eq!(Ok(51), eval_expression("1 + (2 * 3) + (4 * (5 + 6))"));
+        assert_eq!(Ok(26), eval_expression("2 * 3 + (4 * 5)"));
+        assert_eq!(Ok(437), eval_expression("5 + (8 * 3 + 9 + 3 * 4 * 3)"));
         assert_eq!(
-            "669060",
-            eval_expression_with_add_precedence("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")
+            Ok(12240),
+            eval_expression("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")
         );
         assert_eq!(
-            "23340",
-            eval_expression_with_add_precedence("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")
+            Ok(13632),
+            eval_expression("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")
+        );
+        assert_eq!(Ok(11), eval_expression("((5 + 6))"));
+        assert_eq!(Ok(150), eval_expression("(12 + 3) * 10"));
+        assert_eq!(Ok(1230), eval_expression("(12 + (3 * 100)) + 918"));
+        assert_eq!(
+            Err(Error::input("'9223372036854775807 + 1' overflows")),
+            eval_expression("9223372036854775807 + 1")
         );
     }
 
     #[test]
-    fn test_simplify_expression() {
+    fn test_parts() {
+        let day = Day18::parse("1 + 2 * 3\n3 * 4").unwrap();
+        assert_eq!(Some("21".to_string()), day.part_one());
+        assert_eq!(Some("21".to_string()), day.part_two());
+
+        // Only the second part overflows, so the first can still be solved.
+        let day = Day18::parse("3037000500 * 3037000499 + 2").unwrap();
         assert_eq!(
-            "6810 + 2 + 4 * 2",
-            simplify_expression(
-                "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
-                eval_expression
-            )
+            Ok(Some("9223372033963249502".to_string())),
+            day.try_part_one()
         );
-        assert_eq!("11", eval_expression("((5 + 6))"));
+        assert_eq!(
+            Err(Error::line(1, "'3037000500 * 3037000501' overflows")),
+            day.try_part_two()
+        );
+        assert_eq!(None, day.part_two());
     }
 
     #[test]
-    fn test_solve_expression() {
-        assert_eq!(71, solve_expression("1 + 2 * 3 + 4 * 5 + 6", |_| 3));
+    fn test_errors_when_solving() {
+        let day = Day18::parse("9223372036854775807 + 0\n\n1").unwrap();
+        assert_eq!(
+            Err(Error::line(3, "the sum of the results overflows")),
+            day.try_part_one()
+        );
         assert_eq!(
-            231,
-            solve_expression("1 + 2 * 3 + 4 * 5 + 6", find_next_add_idx)
+            Err(Error::line(2, "'x' isn't defined")),
+            Day18::parse("1 + 2\nx + 1").unwrap().try_part_two()
         );
     }
 
     #[test]
-    fn test_element_eval() {
+    fn test_eval_expression_with_add_precedence() {
+        assert_eq!(
+            Ok(231),
+            eval_expression_with_add_precedence("1 + 2 * 3 + 4 * 5 + 6")
+        );
+        assert_eq!(
+            Ok(51),
+            eval_expression_with_add_precedence("1 + (2 * 3) + (4 * (5 + 6))")
+        );
+        assert_eq!(
+            Ok(46),
+            eval_expression_with_add_precedence("2 * 3 + (4 * 5)")
+        );
         assert_eq!(
-            Some(Element::new_number(15)),
-            Element::eval(
-                &Element::new_number(7),
-                &Element::new_add(),
-                &Element::new_number(8)
-            )
+            Ok(1445),
+            eval_expression_with_add_precedence("5 + (8 * 3 + 9 + 3 * 4 * 3)")
         );
         assert_eq!(
-            Some(Element::new_number(56)),
-            Element::eval(
-                &Element::new_number(7),
-                &Element::new_mul(),
-                &Element::new_number(8)
-            )
+            Ok(669060),
+            eval_expression_with_add_precedence("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")
         );
         assert_eq!(
-            None,
-            Element::eval(
-                &Element::new_number(7),
-                &Element::new_number(7),
-                &Element::new_number(8)
-            )
+            Ok(23340),
+            eval_expression_with_add_precedence("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")
         );
     }
 
     #[test]
-    fn test_element_from_str() {
-        assert_eq!(Some(Element::new_mul()), Element::from_str(" * "));
-        assert_eq!(Some(Element::new_add()), Element::from_str(" + "));
-        assert_eq!(Some(Element::new_number(15)), Element::from_str("15"));
-        assert_eq!(Some(Element::new_number(-15)), Element::from_str("-15"));
-        assert_eq!(None, Element::from_str(" / "));
+    fn test_evaluate() {
+        let mut variables = HashMap::new();
+        variables.insert("x".to_string(), 4);
+
+        let evaluation = evaluate("x * 2 + 1", &Precedence::addition_first(), &variables).unwrap();
+        assert_eq!("*\n  x\n  +\n    2\n    1", evaluation.tree.tree());
+        assert_eq!(
+            Ok((12, vec!["2 + 1 = 3".to_string(), "4 * 3 = 12".to_string()])),
+            evaluation.result
+        );
+
+        let evaluation = evaluate("y + 1", &Precedence::standard(), &variables).unwrap();
+        assert_eq!(Err(Error::input("'y' isn't defined")), evaluation.value());
+        assert!(evaluate("1 +", &Precedence::standard(), &variables).is_err());
     }
 }
@@ -1,147 +1,149 @@
+use super::lib::grammar::{Grammar, Symbol};
 use super::lib::*;
 
 use std::collections::HashMap;
-use std::collections::HashSet;
 
-extern crate itertools;
-use self::itertools::*;
+type Rules = HashMap<String, Vec<Vec<String>>>;
 
-extern crate regex;
-use self::regex::RegexSet;
-
-type Rules<'a> = HashMap<&'a str, Vec<Vec<String>>>;
+pub struct Day19 {
+    grammar: Grammar,
+    messages: Vec<String>,
+}
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day19.txt";
-    let inputs = read_inputs(&filename);
-    let mut rules = parse_rules(&inputs);
-    let inputs = parse_inputs(&inputs);
+impl Solution for Day19 {
+    fn parse(input: &str) -> Result<Self> {
+        let rules = parse_rules(input)?;
+        if !rules.contains_key("0") {
+            return Err(Error::input("rule 0 is not defined"));
+        }
 
-    let rule_0: HashSet<String> = get_rule("0", &mut rules).iter().cloned().collect();
+        Ok(Day19 {
+            grammar: to_grammar(&rules),
+            messages: parse_inputs(input)?
+                .into_iter()
+                .map(|message| message.to_string())
+                .collect(),
+        })
+    }
 
-    let part_one = Some(
-        inputs
-            .iter()
-            .filter(|input| rule_0.contains(**input))
-            .count()
-            .to_string(),
-    );
+    fn part_one(&self) -> Option<String> {
+        Some(number_of_matching(&self.grammar, &self.messages).to_string())
+    }
 
-    let part_two = Some(number_of_matching_with_looped_rules(&inputs, &rules).to_string());
+    fn part_two(&self) -> Option<String> {
+        let grammar = with_looped_rules(self.grammar.clone())?;
+        Some(number_of_matching(&grammar, &self.messages).to_string())
+    }
+}
 
-    (part_one, part_two)
+fn number_of_matching(grammar: &Grammar, messages: &[String]) -> usize {
+    messages
+        .iter()
+        .filter(|message| grammar.matches("0", message))
+        .count()
 }
 
-fn parse_rules(inputs: &str) -> Rules {
-    inputs
+fn parse_rules(inputs: &str) -> Result<Rules> {
+    let rules: Rules = inputs
         .split_by_blank_lines()
-        .collect::<Vec<&str>>()
-        .get(0)
-        .expect("Expected rules section")
+        .next()
+        .ok_or_else(|| Error::input("expected a rules section"))?
         .lines()
         .map(|line| {
-            let line: Vec<_> = line.split(':').collect();
-            let rule_number = *line.get(0).expect("Expected rule number");
-            let rule_dependencies = line.get(1).expect("Expected rule dependencies");
-
-            let rule_dependencies: Vec<Vec<String>> = rule_dependencies
+            let (rule_number, rule_dependencies) = line.split_once(':').ok_or_else(|| {
+                Error::at(
+                    inputs,
+                    line,
+                    format!("expected '<rule number>: <rules>', found '{}'", line),
+                )
+            })?;
+            parse_token::<usize>(inputs, rule_number, "a rule number")?;
+
+            let rule_dependencies = rule_dependencies
                 .split('|')
-                .map(|rule| rule.split_whitespace().map(|x| x.to_string()).collect())
-                .collect();
+                .map(|rule| {
+                    rule.split_whitespace()
+                        .map(|x| {
+                            let is_literal = x.len() > 2 && x.starts_with('"') && x.ends_with('"');
+                            if !is_literal {
+                                parse_token::<usize>(
+                                    inputs,
+                                    x,
+                                    "a rule number or a quoted literal",
+                                )?;
+                            }
+                            Ok(x.to_string())
+                        })
+                        .collect::<Result<Vec<String>>>()
+                })
+                .collect::<Result<Vec<Vec<String>>>>()?;
 
-            (rule_number, rule_dependencies)
+            Ok((rule_number.to_string(), rule_dependencies))
         })
-        .collect()
+        .collect::<Result<Rules>>()?;
+
+    let missing_rule = rules
+        .values()
+        .flatten()
+        .flatten()
+        .find(|rule| !rule.starts_with('"') && !rules.contains_key(*rule));
+    if let Some(missing_rule) = missing_rule {
+        return Err(Error::input(format!(
+            "rule {} is not defined",
+            missing_rule
+        )));
+    }
+    Ok(rules)
 }
 
-fn parse_inputs(inputs: &str) -> Vec<&str> {
+fn parse_inputs(inputs: &str) -> Result<Vec<&str>> {
     inputs
         .split_by_blank_lines()
-        .collect::<Vec<&str>>()
-        .get(1)
-        .expect("Expected inputs section")
-        .lines()
-        .collect()
-}
-
-fn get_rule(rule: &str, rules: &mut Rules) -> Vec<String> {
-    let r = rules.clone();
-    let rule_dependencies = r.get(rule).unwrap();
-
-    rule_dependencies
-        .iter()
-        .map(|dependent_rule| {
-            dependent_rule
-                .iter()
-                .map(|sequence| {
-                    if sequence.starts_with('"') {
-                        vec![sequence.to_string().replace("\"", "")]
-                    } else {
-                        let sequence_rule = get_rule(sequence, rules);
-                        if let Some(x) = rules.get_mut(sequence.as_str()) {
-                            *x = sequence_rule
-                                .iter()
-                                .map(|x| {
-                                    let result = format!("\"{}\"", x);
-                                    vec![result]
-                                })
-                                .collect();
-                        };
-                        sequence_rule
-                    }
-                })
-                .collect::<Vec<Vec<String>>>()
-        })
-        .map(|x| cartesian_product_rules(&x))
-        .flatten()
-        .collect()
+        .nth(1)
+        .map(|messages| messages.lines().collect())
+        .ok_or_else(|| Error::input("expected a messages section"))
 }
 
-fn cartesian_product_rules(rules: &[Vec<String>]) -> Vec<String> {
-    rules
-        .iter()
-        .skip(1)
-        .fold(rules[0].clone(), |result, element| {
-            result
-                .into_iter()
-                .cartesian_product(element.iter())
-                .map(|(left, right)| [left, right.to_string()].concat())
-                .collect()
-        })
+fn to_grammar(rules: &Rules) -> Grammar {
+    let mut grammar = Grammar::new();
+    for (name, alternatives) in rules {
+        let alternatives = alternatives
+            .iter()
+            .map(|sequence| {
+                sequence
+                    .iter()
+                    .map(|symbol| {
+                        if symbol.starts_with('"') {
+                            Symbol::Literal(symbol.trim_matches('"').to_string())
+                        } else {
+                            Symbol::Rule(symbol.to_string())
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        grammar.insert(name, alternatives);
+    }
+    grammar
 }
 
-fn number_of_matching_with_looped_rules(inputs: &[&str], rules: &Rules) -> usize {
-    let rule_42 = rules
-        .get("42")
-        .unwrap()
-        .iter()
-        .flatten()
-        .map(|x| x.replace("\"", ""))
-        .join("|");
-    let rule_31 = rules
-        .get("31")
-        .unwrap()
-        .iter()
-        .flatten()
-        .map(|x| x.replace("\"", ""))
-        .join("|");
-
-    // Rust regex doesn't support recursive statements, so we're manually making sure
-    // the last 42 appears the same amount of times as 31.
-    // The number 4 comes from manually verifying the smallest number that gives me the same result for my input.
-    let regexes = (1..=4)
-        .map(|i| {
-            let rule_8_re_pattern = format!("({})+", rule_42);
-            let rule_11_re_pattern = format!("({}){{{}}}({}){{{}}}", rule_42, i, rule_31, i);
-            format!("^{}{}$", rule_8_re_pattern, rule_11_re_pattern)
-        })
-        .collect::<Vec<String>>();
+/// Replaces rules 8 and 11 with `8: 42 | 42 8` and `11: 42 31 | 42 11 31`, if 42 and 31 are
+/// defined.
+fn with_looped_rules(mut grammar: Grammar) -> Option<Grammar> {
+    if !grammar.contains("42") || !grammar.contains("31") {
+        return None;
+    }
 
-    let regexset = RegexSet::new(&regexes).unwrap();
-    inputs
-        .iter()
-        .filter(|input| regexset.is_match(input))
-        .count()
+    let rule = |name: &str| Symbol::Rule(name.to_string());
+    grammar.insert("8", vec![vec![rule("42")], vec![rule("42"), rule("8")]]);
+    grammar.insert(
+        "11",
+        vec![
+            vec![rule("42"), rule("31")],
+            vec![rule("42"), rule("11"), rule("31")],
+        ],
+    );
+    Some(grammar)
 }
 
 #[cfg(test)]
@@ -217,58 +219,120 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba";
     #[test]
     fn test_parse_rules() {
         let mut expected_result = HashMap::new();
-        expected_result.insert("0", vec![vec!["1".to_string(), "2".to_string()]]);
-        expected_result.insert("1", vec![vec!["\"a\"".to_string()]]);
         expected_result.insert(
-            "2",
+            "0".to_string(),
+            vec![vec!["1".to_string(), "2".to_string()]],
+        );
+        expected_result.insert("1".to_string(), vec![vec!["\"a\"".to_string()]]);
+        expected_result.insert(
+            "2".to_string(),
             vec![
                 vec!["1".to_string(), "3".to_string()],
                 vec!["3".to_string(), "1".to_string()],
             ],
         );
-        expected_result.insert("3", vec![vec!["\"b\"".to_string()]]);
+        expected_result.insert("3".to_string(), vec![vec!["\"b\"".to_string()]]);
 
-        assert_eq!(expected_result, parse_rules(TEST_CASE_1));
+        assert_eq!(Ok(expected_result), parse_rules(TEST_CASE_1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                1,
+                "expected '<rule number>: <rules>', found '1 \"a\"'"
+            )),
+            Day19::parse("0: 1 1\n1 \"a\"\n\naa").err()
+        );
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                4,
+                "expected a rule number or a quoted literal, found 'a'"
+            )),
+            Day19::parse("0: 1 1\n1: a\n\naa").err()
+        );
+        assert_eq!(
+            Some(Error::input("rule 2 is not defined")),
+            Day19::parse("0: 1 2\n1: \"a\"\n\naa").err()
+        );
+        assert_eq!(
+            Some(Error::input("expected a messages section")),
+            Day19::parse("0: 1 1\n1: \"a\"").err()
+        );
     }
 
     #[test]
     fn test_parse_inputs() {
-        let inputs = parse_inputs(TEST_CASE_2);
+        let inputs = parse_inputs(TEST_CASE_2).unwrap();
         let expected = vec!["ababbb", "bababa", "abbbab", "aaabbb", "aaaabbb"];
 
         assert_eq!(expected, inputs);
     }
 
     #[test]
-    fn test_get_rule() {
-        let mut rules = parse_rules(TEST_CASE_1);
-        assert_eq!(vec!["a"], get_rule("1", &mut rules));
-        assert_eq!(vec!["ab", "ba"], get_rule("2", &mut rules));
-        assert_eq!(vec!["aab", "aba"], get_rule("0", &mut rules));
-        let mut rules = parse_rules(TEST_CASE_2);
-        assert_eq!(
-            vec!["aaaabb", "aaabab", "abbabb", "abbbab", "aabaab", "aabbbb", "abaaab", "ababbb"],
-            get_rule("0", &mut rules)
-        )
+    fn test_to_grammar() {
+        let rules = parse_rules(TEST_CASE_1).unwrap();
+        let grammar = to_grammar(&rules);
+
+        assert!(grammar.matches("0", "aab"));
+        assert!(grammar.matches("0", "aba"));
+        assert!(!grammar.matches("0", "abb"));
+        assert!(grammar.matches("2", "ba"));
+
+        let grammar = to_grammar(&parse_rules(TEST_CASE_2).unwrap());
+        for message in &[
+            "aaaabb", "aaabab", "abbabb", "abbbab", "aabaab", "aabbbb", "abaaab", "ababbb",
+        ] {
+            assert!(grammar.matches("0", message));
+        }
+        assert!(!grammar.matches("0", "bababa"));
+        assert!(!grammar.matches("0", "aaaabbb"));
     }
 
     #[test]
-    fn test_number_of_matching_with_looped_rules() {
-        let mut rules = parse_rules(TEST_CASE_3);
-        let inputs = parse_inputs(TEST_CASE_3);
-        get_rule("0", &mut rules);
-
-        assert_eq!(12, number_of_matching_with_looped_rules(&inputs, &rules));
+    fn test_part_one() {
+        assert_eq!(
+            Some("2".to_string()),
+            Day19::parse(TEST_CASE_2).unwrap().part_one()
+        );
+        assert_eq!(
+            Some("3".to_string()),
+            Day19::parse(TEST_CASE_3).unwrap().part_one()
+        );
     }
 
     #[test]
-    fn test_cartesian_product_rules() {
-        let a = vec!["ab".to_string()];
-        let b = vec!["cd".to_string(), "ef".to_string()];
-        let rule = vec![a, b];
-
-        let expected = vec!["abcd", "abef"];
+    fn test_part_two() {
+        assert_eq!(
+            Some("12".to_string()),
+            Day19::parse(TEST_CASE_3).unwrap().part_two()
+        );
+        assert_eq!(None, Day19::parse(TEST_CASE_2).unwrap().part_two());
+    }
 
-        assert_eq!(expected, cartesian_product_rules(&rule));
+    #[test]
+    fn test_with_looped_rules() {
+        let (rule_42, rule_31) = ("babbb", "bbaba");
+        let grammar = with_looped_rules(to_grammar(&parse_rules(TEST_CASE_3).unwrap())).unwrap();
+        assert!(grammar.matches("42", rule_42));
+        assert!(grammar.matches("31", rule_31));
+
+        // More repetitions of 11 than any fixed limit would allow for.
+        for count in &[1, 5, 12] {
+            let message = format!(
+                "{}{}{}",
+                rule_42,
+                rule_42.repeat(*count),
+                rule_31.repeat(*count)
+            );
+            assert!(grammar.matches("0", &message));
+        }
+        let message = format!("{}{}", rule_42.repeat(5), rule_31.repeat(5));
+        assert!(!grammar.matches("0", &message));
+        let message = format!("{}{}", rule_42.repeat(6), rule_31);
+        assert!(grammar.matches("0", &message));
     }
 }
@@ -1,27 +1,55 @@
 use super::lib::*;
 use std::collections::HashMap;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day2.txt";
-    let inputs = read_inputs(&filename);
-    let inputs: Vec<&str> = inputs.lines().collect();
+type PasswordEntry = (usize, usize, char, String);
 
-    let part_one = number_of_valid_passwords(&inputs, valid_password);
+pub struct Day2 {
+    passwords: Vec<PasswordEntry>,
+}
+
+impl Solution for Day2 {
+    fn parse(input: &str) -> Result<Self> {
+        let passwords = input
+            .lines()
+            .map(|line| match parse_password_entry(line) {
+                Some((min, max, _, _)) if min == 0 || min > max => Err(Error::at(
+                    input,
+                    line,
+                    format!("expected 1 <= min <= max, found {}-{}", min, max),
+                )),
+                Some(entry) => Ok(entry),
+                None => Err(Error::at(
+                    input,
+                    line,
+                    format!(
+                        "expected '<min>-<max> <letter>: <password>', found '{}'",
+                        line
+                    ),
+                )),
+            })
+            .collect::<Result<Vec<PasswordEntry>>>()?;
+        Ok(Day2 { passwords })
+    }
 
-    let part_two = number_of_valid_passwords(&inputs, valid_password_part_two);
+    fn part_one(&self) -> Option<String> {
+        Some(number_of_valid_passwords(&self.passwords, valid_password).to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        Some(number_of_valid_passwords(&self.passwords, valid_password_part_two).to_string())
+    }
+}
 
-    (Some(part_one.to_string()), Some(part_two.to_string()))
+fn parse_password_entry(line: &str) -> Option<PasswordEntry> {
+    scan_fmt!(line, "{d}-{d} {[a-z]}: {}", usize, usize, char, String).ok()
 }
 
 fn number_of_valid_passwords(
-    passwords: &[&str],
+    passwords: &[PasswordEntry],
     validator_function: fn(&usize, &usize, &char, &str) -> bool,
 ) -> usize {
     passwords
         .iter()
-        .map(|password| {
-            scan_fmt!(password, "{d}-{d} {[a-z]}: {}", usize, usize, char, String).unwrap()
-        })
         .filter(|(min, max, character, password)| validator_function(min, max, character, password))
         .count()
 }
@@ -40,17 +68,11 @@ fn valid_password_part_two(
     character: &char,
     password: &str,
 ) -> bool {
-    let char_position1 = password
-        .chars()
-        .nth(*position_1 - 1)
-        .expect("Expected a character in position 1");
-    let char_position2 = password
-        .chars()
-        .nth(*position_2 - 1)
-        .expect("Expected a character in position 2");
+    let char_position1 = password.chars().nth(*position_1 - 1);
+    let char_position2 = password.chars().nth(*position_2 - 1);
 
     char_position1 != char_position2
-        && (char_position1 == *character || char_position2 == *character)
+        && (char_position1 == Some(*character) || char_position2 == Some(*character))
 }
 
 fn character_distribution(line: &str) -> HashMap<char, usize> {
@@ -68,31 +90,56 @@ fn character_distribution(line: &str) -> HashMap<char, usize> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_password_entry() {
+        assert_eq!(
+            Some((1, 3, 'a', "abcde".to_string())),
+            parse_password_entry("1-3 a: abcde")
+        );
+        assert_eq!(None, parse_password_entry("1-3 abcde"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(2, 1, "expected 1 <= min <= max, found 0-3")),
+            Day2::parse("1-3 a: abcde\n0-3 b: cdefg").err()
+        );
+        assert_eq!(
+            Some(Error::parse(
+                1,
+                1,
+                "expected '<min>-<max> <letter>: <password>', found '1-3: abcde'"
+            )),
+            Day2::parse("1-3: abcde").err()
+        );
+    }
+
     #[test]
     fn test_number_of_valid_passwords() {
-        let passwords = vec!["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"];
+        let passwords: Vec<PasswordEntry> =
+            vec!["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"]
+                .into_iter()
+                .filter_map(parse_password_entry)
+                .collect();
         assert_eq!(2, number_of_valid_passwords(&passwords, valid_password));
     }
     #[test]
     fn test_valid_password() {
-        assert_eq!(true, valid_password(&1usize, &3usize, &'a', "abcde"));
-        assert_eq!(false, valid_password(&2usize, &3usize, &'a', "abcde"));
+        assert!(valid_password(&1usize, &3usize, &'a', "abcde"));
+        assert!(!valid_password(&2usize, &3usize, &'a', "abcde"));
     }
 
     #[test]
     fn test_valid_password_part_two() {
-        assert_eq!(
-            true,
-            valid_password_part_two(&1usize, &3usize, &'a', "abcde")
-        );
-        assert_eq!(
-            false,
-            valid_password_part_two(&1usize, &3usize, &'b', "cdefg")
-        );
-        assert_eq!(
-            false,
-            valid_password_part_two(&1usize, &3usize, &'c', "ccccccccc")
-        );
+        assert!(valid_password_part_two(&1usize, &3usize, &'a', "abcde"));
+        assert!(!valid_password_part_two(&1usize, &3usize, &'b', "cdefg"));
+        assert!(!valid_password_part_two(
+            &1usize,
+            &3usize,
+            &'c',
+            "ccccccccc"
+        ));
     }
 
     #[test]
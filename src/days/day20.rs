@@ -1,47 +1,110 @@
+use super::lib::pattern::{self, Pattern};
 use super::lib::*;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 extern crate itertools;
 use self::itertools::*;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day20.txt";
-    let inputs = read_inputs(&filename);
-    let tiles = parse_tiles(&inputs);
+lazy_static! {
+    static ref SEA_MONSTER: Pattern = Pattern::parse(
+        concat!(
+            "                  # \n",
+            "#    ##    ##    ###\n",
+            " #  #  #  #  #  #   ",
+        ),
+        ' '
+    )
+    .unwrap();
+}
+
+pub struct Day20 {
+    tiles: HashMap<usize, Grid<char>>,
+}
+
+impl Solution for Day20 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Day20 {
+            tiles: parse_tiles(input)?,
+        })
+    }
+
+    fn part_one(&self) -> Option<String> {
+        let corner_tiles = find_corner_tiles(&self.tiles);
+        Some(corner_tiles.iter().product::<usize>().to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        let image = self.image()?;
+        Some(water_roughness(&image).to_string())
+    }
+}
 
-    let corner_tiles = find_corner_tiles(&tiles);
-    let part_one = Some(corner_tiles.iter().product::<usize>().to_string());
-    let part_two = None;
+impl Day20 {
+    /// The picture the tiles make once they're put together and their borders are removed.
+    pub fn image(&self) -> Option<Grid<char>> {
+        arrange_tiles(&self.tiles).and_then(|arrangement| join_tiles(&arrangement))
+    }
+}
 
-    (part_one, part_two)
+/// How many '#' are left in the image once every sea monster, whichever way round, is masked.
+fn water_roughness(image: &Grid<char>) -> usize {
+    let sea_monsters = SEA_MONSTER.find_in_any_orientation(image);
+    pattern::mask(image, &sea_monsters, 'O')
+        .cells()
+        .filter(|c| **c == '#')
+        .count()
 }
 
-fn parse_tiles(inputs: &str) -> HashMap<usize, String> {
-    inputs
+fn parse_tiles(inputs: &str) -> Result<HashMap<usize, Grid<char>>> {
+    let mut tiles = HashMap::new();
+    for tile in inputs
         .split_by_blank_lines()
-        .map(|tile| {
-            let mut tile_lines = tile.lines();
-            let tile_number = tile_lines
-                .next()
-                .unwrap()
-                .split_whitespace()
-                .nth(1)
-                .unwrap()
-                .trim_end_matches(':')
-                .parse::<usize>()
-                .unwrap();
-            let tile: String = tile_lines
-                .map(|line| format!("{}\n", line))
-                .collect::<String>()
-                .trim()
-                .to_string();
-            (tile_number, tile)
-        })
-        .collect()
+        .filter(|tile| !tile.trim().is_empty())
+    {
+        let mut tile_lines = tile.lines();
+        let header = tile_lines.next().unwrap_or(tile);
+        let tile_number = header
+            .strip_prefix("Tile ")
+            .and_then(|number| number.strip_suffix(':'))
+            .ok_or_else(|| {
+                Error::at(
+                    inputs,
+                    header,
+                    format!("expected 'Tile <number>:', found '{}'", header),
+                )
+            })?;
+        let tile_number = parse_token(inputs, tile_number, "a tile number")?;
+
+        let rows: Vec<&str> = tile_lines.collect();
+        if let Some(row) = rows.iter().find(|row| row.len() != rows.len()) {
+            let message = format!(
+                "expected a square tile with rows of width {}, found '{}'",
+                rows.len(),
+                row
+            );
+            return Err(Error::at(inputs, row, message));
+        }
+
+        let body = tile[header.len()..].trim_start_matches('\n');
+        let grid = Grid::parse_within(
+            inputs,
+            body,
+            |c| if c == '#' || c == '.' { Some(c) } else { None },
+            "'#' or '.'",
+        )?;
+        if tiles.insert(tile_number, grid).is_some() {
+            return Err(Error::at(
+                inputs,
+                header,
+                format!("tile {} is defined twice", tile_number),
+            ));
+        }
+    }
+    Ok(tiles)
 }
 
-fn find_corner_tiles(tiles: &HashMap<usize, String>) -> Vec<usize> {
+fn find_corner_tiles(tiles: &HashMap<usize, Grid<char>>) -> Vec<usize> {
     let tiles_sides: HashMap<usize, Vec<String>> = tiles
         .iter()
         .map(|(tile_number, tile)| (*tile_number, all_tile_sides_combinations(tile)))
@@ -66,23 +129,16 @@ fn find_corner_tiles(tiles: &HashMap<usize, String>) -> Vec<usize> {
         .collect()
 }
 
-fn all_tile_sides_combinations(tile: &str) -> Vec<String> {
-    let tile: Vec<Vec<char>> = tile
-        .lines()
-        .map(|x| x.chars().collect::<Vec<char>>())
-        .collect();
-    let tile_side_len = &tile[0].len();
-
-    let top_side: String = tile.get(0).unwrap().iter().collect();
-    let right_side: String = tile.iter().map(|x| x[tile_side_len - 1]).collect();
-    let bottom_side: String = tile.get(tile_side_len - 1).unwrap().iter().collect();
-    let left_side: String = tile.iter().map(|x| x[0]).collect();
+fn all_tile_sides_combinations(tile: &Grid<char>) -> Vec<String> {
+    let top_side: String = tile.row(0).iter().collect();
+    let right_side: String = tile.column(tile.width() - 1).collect();
+    let bottom_side: String = tile.row(tile.height() - 1).iter().collect();
+    let left_side: String = tile.column(0).collect();
 
     let all_sides = vec![top_side, right_side, bottom_side, left_side];
     all_sides
         .into_iter()
-        .map(|side| vec![side.clone(), side.chars().rev().collect()])
-        .flatten()
+        .flat_map(|side| vec![side.clone(), side.chars().rev().collect()])
         .collect()
 }
 
@@ -100,6 +156,116 @@ fn convert_to_side_tiles_hashmap(
         })
 }
 
+/// Places every tile in a square, turned and flipped so that each of its edges matches the edge
+/// of the tile next to it. Returns the number and orientation of each tile, row by row.
+pub fn arrange_tiles(tiles: &HashMap<usize, Grid<char>>) -> Option<Grid<(usize, Grid<char>)>> {
+    let size = (1..=tiles.len()).find(|size| size * size >= tiles.len())?;
+    if size * size != tiles.len() {
+        return None;
+    }
+
+    let orientations: HashMap<usize, Vec<Grid<char>>> = tiles
+        .iter()
+        .map(|(tile_number, tile)| (*tile_number, tile.orientations()))
+        .collect();
+    let tiles_sides: HashMap<usize, Vec<String>> = tiles
+        .iter()
+        .map(|(tile_number, tile)| (*tile_number, all_tile_sides_combinations(tile)))
+        .collect();
+    let side_to_tiles = convert_to_side_tiles_hashmap(&tiles_sides);
+
+    let mut placed = Vec::with_capacity(tiles.len());
+    let mut used = HashSet::new();
+    if !place_next_tile(size, &orientations, &side_to_tiles, &mut placed, &mut used) {
+        return None;
+    }
+    let placed = placed
+        .into_iter()
+        .map(|(tile_number, tile)| (tile_number, tile.clone()))
+        .collect();
+    Some(Grid::new(size, size, placed))
+}
+
+/// Fills the next position, row by row, backtracking when no tile fits there.
+fn place_next_tile<'a>(
+    size: usize,
+    orientations: &'a HashMap<usize, Vec<Grid<char>>>,
+    side_to_tiles: &HashMap<String, Vec<usize>>,
+    placed: &mut Vec<(usize, &'a Grid<char>)>,
+    used: &mut HashSet<usize>,
+) -> bool {
+    let idx = placed.len();
+    if idx == size * size {
+        return true;
+    }
+    let left = if !idx.is_multiple_of(size) {
+        Some(placed[idx - 1].1)
+    } else {
+        None
+    };
+    let above = if idx >= size {
+        Some(placed[idx - size].1)
+    } else {
+        None
+    };
+
+    // Only the tiles that share an edge with a neighbour can go next to it.
+    let candidates: Vec<usize> = match (left, above) {
+        (Some(tile), _) => tiles_with_side(side_to_tiles, tile.column(tile.width() - 1).collect()),
+        (None, Some(tile)) => {
+            tiles_with_side(side_to_tiles, tile.row(tile.height() - 1).iter().collect())
+        }
+        (None, None) => orientations.keys().copied().sorted().collect(),
+    };
+
+    for tile_number in candidates {
+        if used.contains(&tile_number) {
+            continue;
+        }
+        for tile in &orientations[&tile_number] {
+            let fits_left =
+                left.is_none_or(|left| left.column(left.width() - 1).eq(tile.column(0)));
+            let fits_above = above.is_none_or(|above| above.row(above.height() - 1) == tile.row(0));
+            if !fits_left || !fits_above {
+                continue;
+            }
+
+            placed.push((tile_number, tile));
+            used.insert(tile_number);
+            if place_next_tile(size, orientations, side_to_tiles, placed, used) {
+                return true;
+            }
+            placed.pop();
+            used.remove(&tile_number);
+        }
+    }
+    false
+}
+
+fn tiles_with_side(side_to_tiles: &HashMap<String, Vec<usize>>, side: String) -> Vec<usize> {
+    side_to_tiles.get(&side).cloned().unwrap_or_default()
+}
+
+/// Puts the arranged tiles together into one image, without the border of each tile.
+pub fn join_tiles(arrangement: &Grid<(usize, Grid<char>)>) -> Option<Grid<char>> {
+    let tile_size = arrangement.get(0, 0)?.1.width();
+    if tile_size < 3 {
+        return None;
+    }
+    let inner_size = tile_size - 2;
+
+    Some(Grid::from_fn(
+        arrangement.width() * inner_size,
+        arrangement.height() * inner_size,
+        |row, col| {
+            let (_, tile) = arrangement.get(row / inner_size, col / inner_size).unwrap();
+            *tile
+                .get(row % inner_size + 1, col % inner_size + 1)
+                .unwrap()
+        },
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,17 +400,70 @@ Tile 3079:
 ..#.#..#.#
 #...##.#..";
 
+    const IMAGE: &str = ".#.#..#.##...#.##..#####
+###....#.#....#..#......
+##.##.###.#.#..######...
+###.#####...#.#####.#..#
+##.#....#.##.####...#.##
+...########.#....#####.#
+....#..#...##..#.#.###..
+.####...#..#.....#......
+#..#.##..#..###.#.##....
+#.####..#.####.#.#.###..
+###.#.#...#.######.#..##
+#.####....##..########.#
+##..##.#...#...#.#.#.#..
+...#..#..#.#.##..###.###
+.#.#....#.##.#...###.##.
+###.#...#..#.##.######..
+.#.#.###.##.##.#..#.##..
+.####.###.#...###.#..#.#
+..#.#..#..#.#.#.####.###
+#..####...#.#.#.###.###.
+#####..#####...###....##
+#.##..#..#...#..####...#
+.#.###..##..##..####.##.
+...###...##...#...#..###";
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                1,
+                1,
+                "expected 'Tile <number>:', found 'Tile 12'"
+            )),
+            Day20::parse("Tile 12\n#.\n.#").err()
+        );
+        assert_eq!(
+            Some(Error::parse(
+                3,
+                1,
+                "expected a square tile with rows of width 2, found '.#.'"
+            )),
+            Day20::parse("Tile 12:\n#.\n.#.").err()
+        );
+        assert_eq!(
+            Some(Error::parse(3, 2, "expected '#' or '.', found 'x'")),
+            Day20::parse("Tile 12:\n#.\n.x").err()
+        );
+        assert_eq!(
+            Some(Error::parse(5, 1, "tile 12 is defined twice")),
+            Day20::parse("Tile 12:\n#.\n.#\n\nTile 12:\n..\n##").err()
+        );
+    }
+
     #[test]
     fn test_parse_tiles() {
-        let parsed_tiles = parse_tiles(TEST_CASE_1);
+        let parsed_tiles = parse_tiles(TEST_CASE_1).unwrap();
         assert_eq!(9, parsed_tiles.len());
-        assert_eq!(TILE_2311, parsed_tiles.get(&2311).unwrap());
-        assert_eq!(TILE_1951, parsed_tiles.get(&1951).unwrap());
+        assert_eq!(TILE_2311, parsed_tiles.get(&2311).unwrap().to_string());
+        assert_eq!(TILE_1951, parsed_tiles.get(&1951).unwrap().to_string());
     }
 
     #[test]
     fn test_find_corner_tiles() {
-        let tiles = parse_tiles(TEST_CASE_1);
+        let tiles = parse_tiles(TEST_CASE_1).unwrap();
         assert_eq!(vec![1171, 1951, 2971, 3079], find_corner_tiles(&tiles));
     }
 
@@ -261,12 +480,53 @@ Tile 3079:
             ".#..#####.",
         ];
 
-        assert_eq!(expected_sides, all_tile_sides_combinations(TILE_2311));
+        assert_eq!(
+            expected_sides,
+            all_tile_sides_combinations(&Grid::parse(TILE_2311, Some, "a pixel").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_arrange_tiles() {
+        let tiles = parse_tiles(TEST_CASE_1).unwrap();
+        let arrangement = arrange_tiles(&tiles).unwrap();
+        let corners: Vec<usize> = [(0, 0), (0, 2), (2, 0), (2, 2)]
+            .iter()
+            .map(|(row, col)| arrangement.get(*row, *col).unwrap().0)
+            .sorted()
+            .collect();
+
+        assert_eq!((3, 3), (arrangement.width(), arrangement.height()));
+        assert_eq!(vec![1171, 1951, 2971, 3079], corners);
+
+        let mut missing_tile = tiles.clone();
+        missing_tile.remove(&2311);
+        assert_eq!(None, arrange_tiles(&missing_tile));
+    }
+
+    #[test]
+    fn test_image() {
+        let image = Day20::parse(TEST_CASE_1).unwrap().image().unwrap();
+        let expected_image = Grid::parse(IMAGE, Some, "a pixel").unwrap();
+
+        assert!(image.orientations().contains(&expected_image));
+    }
+
+    #[test]
+    fn test_sea_monsters() {
+        let image = Grid::parse(IMAGE, Some, "a pixel").unwrap();
+
+        assert_eq!(2, SEA_MONSTER.find_in_any_orientation(&image).len());
+        assert_eq!(273, water_roughness(&image));
+        assert_eq!(
+            Some("273".to_string()),
+            Day20::parse(TEST_CASE_1).unwrap().part_two()
+        );
     }
 
     #[test]
     fn test_convert_to_side_tiles_hashmap() {
-        let tiles = parse_tiles(TEST_CASE_1);
+        let tiles = parse_tiles(TEST_CASE_1).unwrap();
         let tiles: HashMap<usize, Vec<String>> = tiles
             .iter()
             .map(|(tile_number, tile)| (*tile_number, all_tile_sides_combinations(tile)))
@@ -7,24 +7,58 @@ use self::regex::Regex;
 extern crate itertools;
 use self::itertools::*;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day21.txt";
-    let inputs = read_inputs(&filename);
-    let allergens = find_allergens(&inputs);
-    let all_ingredients = get_ingredients(&inputs);
+lazy_static! {
+    static ref RE: Regex = Regex::new(r"((?:\w+ )+)\(contains ((?:\w+(?:, )*)*)\)").unwrap();
+}
+
+pub struct Day21 {
+    allergens: HashMap<String, String>,
+    ingredients: Vec<HashSet<String>>,
+}
+
+impl Solution for Day21 {
+    fn parse(input: &str) -> Result<Self> {
+        if let Some(line) = input.lines().find(|line| {
+            !RE.find(line)
+                .is_some_and(|m| m.start() == 0 && m.end() == line.len())
+        }) {
+            let message = format!(
+                "expected '<ingredients> (contains <allergens>)', found '{}'",
+                line
+            );
+            return Err(Error::at(input, line, message));
+        }
+
+        let allergens = find_allergens(input).ok_or_else(|| {
+            Error::input("expected every allergen to match exactly one ingredient")
+        })?;
+        Ok(Day21 {
+            allergens,
+            ingredients: get_ingredients(input)
+                .into_iter()
+                .map(|line| line.into_iter().map(|x| x.to_string()).collect())
+                .collect(),
+        })
+    }
 
-    let part_one =
-        Some(number_of_ingredients_with_no_allergens(&all_ingredients, &allergens).to_string());
-    let part_two = Some(get_canonical_dangerous_ingredient_list(&allergens));
+    fn part_one(&self) -> Option<String> {
+        let ingredients: Vec<HashSet<&str>> = self
+            .ingredients
+            .iter()
+            .map(|line| line.iter().map(|x| x.as_str()).collect())
+            .collect();
+        Some(number_of_ingredients_with_no_allergens(&ingredients, &self.allergens).to_string())
+    }
 
-    (part_one, part_two)
+    fn part_two(&self) -> Option<String> {
+        Some(get_canonical_dangerous_ingredient_list(&self.allergens))
+    }
 }
 
-fn find_allergens(inputs: &str) -> HashMap<String, String> {
-    let re = Regex::new(r"((?:\w+ )+)\(contains ((?:\w+(?:, )*)*)\)").unwrap();
+fn find_allergens(inputs: &str) -> Option<HashMap<String, String>> {
     let mut result: HashMap<String, HashSet<String>> = HashMap::new();
 
-    for cap in re.captures_iter(inputs) {
+    for cap in RE.captures_iter(inputs) {
         let ingredients = cap
             .get(1)
             .unwrap()
@@ -32,8 +66,7 @@ fn find_allergens(inputs: &str) -> HashMap<String, String> {
             .split_whitespace()
             .map(|x| x.to_string())
             .collect::<HashSet<String>>();
-        let _allergens = cap
-            .get(2)
+        cap.get(2)
             .unwrap()
             .as_str()
             .split(", ")
@@ -52,6 +85,7 @@ fn find_allergens(inputs: &str) -> HashMap<String, String> {
     let mut allergens: HashMap<String, String> = HashMap::new();
 
     while !result.is_empty() {
+        let number_of_allergens = allergens.len();
         result
             .iter()
             .filter(|(_, values)| values.len() == 1)
@@ -65,14 +99,16 @@ fn find_allergens(inputs: &str) -> HashMap<String, String> {
                 (*ingredients).remove(ingredient);
             });
         }
+
+        if allergens.len() == number_of_allergens {
+            return None;
+        }
     }
-    allergens
+    Some(allergens)
 }
 
 fn get_ingredients(inputs: &str) -> Vec<HashSet<&str>> {
-    let re = Regex::new(r"((?:\w+ )+)\(contains ((?:\w+(?:, )*)*)\)").unwrap();
-
-    re.captures_iter(inputs)
+    RE.captures_iter(inputs)
         .filter_map(|captures| captures.get(1))
         .map(|x| x.as_str().split_whitespace().collect())
         .collect()
@@ -82,8 +118,7 @@ fn number_of_ingredients_with_no_allergens(
     all_ingredients: &[HashSet<&str>],
     allergens: &HashMap<String, String>,
 ) -> usize {
-    let allergen_ingredients: Vec<&str> =
-        allergens.iter().map(|(_, value)| value.as_str()).collect();
+    let allergen_ingredients: Vec<&str> = allergens.values().map(|value| value.as_str()).collect();
 
     let all_ingredients_without_allergens: Vec<&str> = all_ingredients
         .iter()
@@ -114,9 +149,27 @@ trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
 sqjhc mxmxvkd sbzzf (contains fish)";
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                1,
+                "expected '<ingredients> (contains <allergens>)', found 'trh fvjkl (dairy)'"
+            )),
+            Day21::parse("mxmxvkd kfcds (contains dairy, fish)\ntrh fvjkl (dairy)").err()
+        );
+        assert_eq!(
+            Some(Error::input(
+                "expected every allergen to match exactly one ingredient"
+            )),
+            Day21::parse("mxmxvkd kfcds (contains dairy)").err()
+        );
+    }
+
     #[test]
     fn test_find_allergens() {
-        let allergens = find_allergens(TEST_CASE_1);
+        let allergens = find_allergens(TEST_CASE_1).unwrap();
         let expected_allergens: HashMap<String, String> =
             [("dairy", "mxmxvkd"), ("fish", "sqjhc"), ("soy", "fvjkl")]
                 .iter()
@@ -146,7 +199,7 @@ sqjhc mxmxvkd sbzzf (contains fish)";
     #[test]
     fn test_number_of_ingredients_with_no_allergens() {
         let ingredients = get_ingredients(TEST_CASE_1);
-        let allergens = find_allergens(TEST_CASE_1);
+        let allergens = find_allergens(TEST_CASE_1).unwrap();
         assert_eq!(
             5,
             number_of_ingredients_with_no_allergens(&ingredients, &allergens)
@@ -155,7 +208,7 @@ sqjhc mxmxvkd sbzzf (contains fish)";
 
     #[test]
     fn test_get_canonical_dangerous_ingredient_list() {
-        let allergens = find_allergens(TEST_CASE_1);
+        let allergens = find_allergens(TEST_CASE_1).unwrap();
         assert_eq!(
             "mxmxvkd,sqjhc,fvjkl",
             get_canonical_dangerous_ingredient_list(&allergens),
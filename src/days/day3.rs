@@ -1,51 +1,61 @@
 use super::lib::*;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day3.txt";
-    let inputs = read_inputs(&filename);
-    let map: Vec<&str> = inputs.lines().collect();
+pub struct Day3 {
+    map: Grid<bool>,
+}
 
-    let delta = (1, 3);
+impl Solution for Day3 {
+    fn parse(input: &str) -> Result<Self> {
+        let map = parse_map(input)?;
+        Ok(Day3 { map })
+    }
 
-    let part_one = get_multiplied_slopes(&map, &[&delta]);
+    fn part_one(&self) -> Option<String> {
+        let delta = (1, 3);
 
-    let deltas = vec![&(1, 1), &(1, 3), &(1, 5), &(1, 7), &(2, 1)];
-    let part_two = get_multiplied_slopes(&map, &deltas);
+        Some(get_multiplied_slopes(&self.map, &[&delta]).to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        let deltas = vec![&(1, 1), &(1, 3), &(1, 5), &(1, 7), &(2, 1)];
 
-    (Some(part_one.to_string()), Some(part_two.to_string()))
+        Some(get_multiplied_slopes(&self.map, &deltas).to_string())
+    }
 }
 
-fn get_multiplied_slopes(map: &[&str], deltas: &[&(usize, usize)]) -> usize {
-    let map_size = (map.len(), map.first().unwrap().len());
+/// Parses the map into a grid of trees.
+fn parse_map(input: &str) -> Result<Grid<bool>> {
+    Grid::parse(
+        input,
+        |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        },
+        "'.' or '#'",
+    )
+}
 
+fn get_multiplied_slopes(map: &Grid<bool>, deltas: &[&(usize, usize)]) -> usize {
     deltas.iter().fold(1, |mult, delta| {
-        mult * get_number_of_trees(&map, &get_coordinates(&delta, &map_size))
+        mult * get_number_of_trees(map, &get_coordinates(delta, map.height()))
     })
 }
 
-fn get_number_of_trees(map: &[&str], coordinates: &[(usize, usize)]) -> usize {
+/// The map repeats to the right, so columns past its width wrap around.
+fn get_number_of_trees(map: &Grid<bool>, coordinates: &[(usize, usize)]) -> usize {
     coordinates
         .iter()
-        .filter(|coordinate| {
-            map.get(coordinate.0)
-                .unwrap()
-                .chars()
-                .nth(coordinate.1)
-                .unwrap()
-                == '#'
-        })
+        .filter(|(row, col)| *map.get_wrapping(*row, *col))
         .count()
 }
 
-fn get_coordinates(delta: &(usize, usize), map_size: &(usize, usize)) -> Vec<(usize, usize)> {
-    let mut col = 0;
-    (0..map_size.0)
+fn get_coordinates(delta: &(usize, usize), height: usize) -> Vec<(usize, usize)> {
+    (0..height)
         .step_by(delta.0)
+        .enumerate()
         .skip(1)
-        .map(|row| {
-            col = (col + delta.1) % map_size.1;
-            (row, col)
-        })
+        .map(|(step, row)| (row, step * delta.1))
         .collect()
 }
 
@@ -53,42 +63,48 @@ fn get_coordinates(delta: &(usize, usize), map_size: &(usize, usize)) -> Vec<(us
 mod tests {
     use super::*;
 
+    const MAP: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
     #[test]
-    fn test_get_number_of_trees() {
-        let map = vec![
-            "..##.......",
-            "#...#...#..",
-            ".#....#..#.",
-            "..#.#...#.#",
-            ".#...##..#.",
-            "..#.##.....",
-            ".#.#.#....#",
-            ".#........#",
-            "#.##...#...",
-            "#...##....#",
-            ".#..#...#.#",
-        ];
+    fn test_parse_map() {
         assert_eq!(
-            7,
-            get_number_of_trees(&map, &get_coordinates(&(1, 3), &(11, 11)))
+            Ok(Grid::new(
+                3,
+                2,
+                vec![false, false, true, true, false, false]
+            )),
+            parse_map("..#\n#..")
         );
+        assert_eq!(
+            Err(Error::parse(2, 2, "expected '.' or '#', found 'x'")),
+            parse_map("..#\n#x.")
+        );
+        assert_eq!(
+            Err(Error::parse(2, 1, "expected a row of width 3, found 2")),
+            parse_map("..#\n#.")
+        );
+        assert!(parse_map("").is_err());
+    }
+
+    #[test]
+    fn test_get_number_of_trees() {
+        let map = parse_map(MAP).unwrap();
+        assert_eq!(7, get_number_of_trees(&map, &get_coordinates(&(1, 3), 11)));
     }
 
     #[test]
     fn test_get_multiplied_slopes() {
-        let map = vec![
-            "..##.......",
-            "#...#...#..",
-            ".#....#..#.",
-            "..#.#...#.#",
-            ".#...##..#.",
-            "..#.##.....",
-            ".#.#.#....#",
-            ".#........#",
-            "#.##...#...",
-            "#...##....#",
-            ".#..#...#.#",
-        ];
+        let map = parse_map(MAP).unwrap();
         let deltas = vec![&(1, 1), &(1, 3), &(1, 5), &(1, 7), &(2, 1)];
         assert_eq!(336, get_multiplied_slopes(&map, &deltas));
     }
@@ -99,20 +115,20 @@ mod tests {
             (1, 3),
             (2, 6),
             (3, 9),
-            (4, 1),
-            (5, 4),
-            (6, 7),
-            (7, 10),
-            (8, 2),
-            (9, 5),
-            (10, 8),
+            (4, 12),
+            (5, 15),
+            (6, 18),
+            (7, 21),
+            (8, 24),
+            (9, 27),
+            (10, 30),
         ];
-        assert_eq!(expected_coordinates, get_coordinates(&(1, 3), &(11, 11)));
+        assert_eq!(expected_coordinates, get_coordinates(&(1, 3), 11));
     }
 
     #[test]
     fn test_get_coordinates_twice_down() {
-        let expected_coordinates = vec![(2, 3), (4, 6), (6, 9), (8, 1), (10, 4)];
-        assert_eq!(expected_coordinates, get_coordinates(&(2, 3), &(11, 11)));
+        let expected_coordinates = vec![(2, 3), (4, 6), (6, 9), (8, 12), (10, 15)];
+        assert_eq!(expected_coordinates, get_coordinates(&(2, 3), 11));
     }
 }
@@ -11,24 +11,41 @@ const HAIR_COLOR: &str = "hcl";
 const EYE_COLOR: &str = "ecl";
 const PASSPORT_ID: &str = "pid";
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day4.txt";
-    let inputs = read_inputs(&filename);
-    let passwords: Vec<&str> = inputs.split_by_blank_lines().collect();
+type Passport = HashMap<String, String>;
 
-    let part_one = count_valid_passwords(&passwords, is_valid_password);
-    let part_two = count_valid_passwords(&passwords, is_valid_complex_password);
+pub struct Day4 {
+    passwords: Vec<Passport>,
+}
+
+impl Solution for Day4 {
+    fn parse(input: &str) -> Result<Self> {
+        if let Some(field) = input.split_whitespace().find(|field| !field.contains(':')) {
+            return Err(Error::at(
+                input,
+                field,
+                format!("expected '<key>:<value>', found '{}'", field),
+            ));
+        }
+
+        let passwords = input.split_by_blank_lines().map(parse_password).collect();
+        Ok(Day4 { passwords })
+    }
 
-    (Some(part_one.to_string()), Some(part_two.to_string()))
+    fn part_one(&self) -> Option<String> {
+        Some(count_valid_passwords(&self.passwords, is_valid_password).to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        Some(count_valid_passwords(&self.passwords, is_valid_complex_password).to_string())
+    }
 }
 
 fn count_valid_passwords(
-    password_lines: &[&str],
-    validator_function: fn(&HashMap<String, String>) -> bool,
+    passwords: &[Passport],
+    validator_function: fn(&Passport) -> bool,
 ) -> usize {
-    password_lines
+    passwords
         .par_iter()
-        .map(|password_line| parse_password(password_line))
         .filter(|password| validator_function(password))
         .count()
 }
@@ -36,19 +53,9 @@ fn count_valid_passwords(
 fn parse_password(password_line: &str) -> HashMap<String, String> {
     password_line
         .split_whitespace()
-        .fold(HashMap::new(), |mut hashmap, element| {
-            let element_parts: Vec<&str> = element.split(':').collect();
-            let key = element_parts
-                .get(0)
-                .expect("Expected to have a key")
-                .to_string();
-            let value = element_parts
-                .get(1)
-                .expect("Expected to have a value")
-                .to_string();
-            hashmap.insert(key, value);
-            hashmap
-        })
+        .filter_map(|element| element.split_once(':'))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
 }
 
 fn is_valid_password(password: &HashMap<String, String>) -> bool {
@@ -88,11 +95,10 @@ fn validate_height(height: &str) -> bool {
     let (height, unit) = scan_fmt_some!(height, "{d}{/(cm|in)/}", u64, String);
     let unit: &str = &unit.unwrap_or_else(|| String::from(""));
 
-    match (height, unit) {
-        (Some(150..=193), "cm") => true,
-        (Some(59..=76), "in") => true,
-        _ => false,
-    }
+    matches!(
+        (height, unit),
+        (Some(150..=193), "cm") | (Some(59..=76), "in")
+    )
 }
 
 fn validate_hair_color(hair_color: &str) -> bool {
@@ -134,12 +140,27 @@ iyr:2011 ecl:brn hgt:59in";
         assert_eq!(
             2,
             count_valid_passwords(
-                &inputs.split_by_blank_lines().collect::<Vec<&str>>(),
+                &inputs
+                    .split_by_blank_lines()
+                    .map(parse_password)
+                    .collect::<Vec<Passport>>(),
                 is_valid_password
             )
         );
     }
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                10,
+                "expected '<key>:<value>', found 'iyr2017'"
+            )),
+            Day4::parse("ecl:gry pid:860033327\nbyr:1937 iyr2017").err()
+        );
+    }
+
     #[test]
     fn test_invalid_complex_passports() {
         let passports = "eyr:1972 cid:100
@@ -158,7 +179,10 @@ pid:3556412378 byr:2007";
         assert_eq!(
             0,
             count_valid_passwords(
-                &passports.split_by_blank_lines().collect::<Vec<&str>>(),
+                &passports
+                    .split_by_blank_lines()
+                    .map(parse_password)
+                    .collect::<Vec<Passport>>(),
                 is_valid_complex_password
             )
         );
@@ -181,7 +205,10 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
         assert_eq!(
             4,
             count_valid_passwords(
-                &passports.split_by_blank_lines().collect::<Vec<&str>>(),
+                &passports
+                    .split_by_blank_lines()
+                    .map(parse_password)
+                    .collect::<Vec<Passport>>(),
                 is_valid_complex_password
             )
         );
@@ -241,75 +268,75 @@ iyr:2011 ecl:brn hgt:59in";
         let mut invalid_password = HashMap::new();
         invalid_password.insert(EYE_COLOR.to_string(), "gry".to_string());
 
-        assert_eq!(true, is_valid_password(&valid_password));
-        assert_eq!(false, is_valid_password(&invalid_password));
+        assert!(is_valid_password(&valid_password));
+        assert!(!is_valid_password(&invalid_password));
     }
 
     #[test]
     fn test_validate_birth_year() {
-        assert_eq!(false, validate_birth_year("1919"));
-        assert_eq!(true, validate_birth_year("1920"));
-        assert_eq!(true, validate_birth_year("2002"));
-        assert_eq!(false, validate_birth_year("2003"));
-        assert_eq!(false, validate_birth_year("2oo2"));
+        assert!(!validate_birth_year("1919"));
+        assert!(validate_birth_year("1920"));
+        assert!(validate_birth_year("2002"));
+        assert!(!validate_birth_year("2003"));
+        assert!(!validate_birth_year("2oo2"));
     }
 
     #[test]
     fn test_validate_issue_year() {
-        assert_eq!(false, validate_issue_year("2009"));
-        assert_eq!(true, validate_issue_year("2010"));
-        assert_eq!(true, validate_issue_year("2020"));
-        assert_eq!(false, validate_issue_year("2021"));
+        assert!(!validate_issue_year("2009"));
+        assert!(validate_issue_year("2010"));
+        assert!(validate_issue_year("2020"));
+        assert!(!validate_issue_year("2021"));
     }
 
     #[test]
     fn test_validate_expiration_year() {
-        assert_eq!(false, validate_expiration_year("2019"));
-        assert_eq!(true, validate_expiration_year("2020"));
-        assert_eq!(true, validate_expiration_year("2030"));
-        assert_eq!(false, validate_expiration_year("2031"));
+        assert!(!validate_expiration_year("2019"));
+        assert!(validate_expiration_year("2020"));
+        assert!(validate_expiration_year("2030"));
+        assert!(!validate_expiration_year("2031"));
     }
 
     #[test]
     fn test_validate_height() {
-        assert_eq!(false, validate_height("149cm"));
-        assert_eq!(true, validate_height("150cm"));
-        assert_eq!(true, validate_height("193cm"));
-        assert_eq!(false, validate_height("194cm"));
+        assert!(!validate_height("149cm"));
+        assert!(validate_height("150cm"));
+        assert!(validate_height("193cm"));
+        assert!(!validate_height("194cm"));
 
-        assert_eq!(false, validate_height("58in"));
-        assert_eq!(true, validate_height("59in"));
-        assert_eq!(true, validate_height("76in"));
-        assert_eq!(false, validate_height("77in"));
+        assert!(!validate_height("58in"));
+        assert!(validate_height("59in"));
+        assert!(validate_height("76in"));
+        assert!(!validate_height("77in"));
 
-        assert_eq!(false, validate_height("190"));
+        assert!(!validate_height("190"));
     }
 
     #[test]
     fn test_validate_hair_color() {
-        assert_eq!(true, validate_hair_color("#123abc"));
-        assert_eq!(false, validate_hair_color("#123abz"));
-        assert_eq!(false, validate_hair_color("#13abc"));
-        assert_eq!(false, validate_hair_color("123abc"));
-        assert_eq!(false, validate_hair_color("123ab"));
+        assert!(validate_hair_color("#123abc"));
+        assert!(!validate_hair_color("#123abz"));
+        assert!(!validate_hair_color("#13abc"));
+        assert!(!validate_hair_color("123abc"));
+        assert!(!validate_hair_color("123ab"));
     }
 
     #[test]
     fn test_validate_eye_color() {
-        assert_eq!(true, validate_eye_color("amb"));
-        assert_eq!(true, validate_eye_color("blu"));
-        assert_eq!(true, validate_eye_color("brn"));
-        assert_eq!(true, validate_eye_color("gry"));
-        assert_eq!(true, validate_eye_color("grn"));
-        assert_eq!(true, validate_eye_color("hzl"));
-        assert_eq!(true, validate_eye_color("oth"));
-        assert_eq!(false, validate_eye_color("ptr"));
+        assert!(validate_eye_color("amb"));
+        assert!(validate_eye_color("blu"));
+        assert!(validate_eye_color("brn"));
+        assert!(validate_eye_color("gry"));
+        assert!(validate_eye_color("grn"));
+        assert!(validate_eye_color("hzl"));
+        assert!(validate_eye_color("oth"));
+        assert!(!validate_eye_color("ptr"));
     }
 
     #[test]
     fn test_passport_id() {
-        assert_eq!(true, validate_passport_id("000000001"));
-        assert_eq!(false, validate_passport_id("0123456789"));
-        assert_eq!(false, validate_passport_id("i23456789"));
+        assert!(validate_passport_id("000000001"));
+        assert!(!validate_passport_id("0123456789"));
+        assert!(!validate_passport_id("i23456789"));
     }
 }
@@ -1,18 +1,38 @@
 use super::lib::*;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day5.txt";
-    let inputs = read_inputs(&filename);
+pub struct Day5 {
+    seat_ids: Vec<usize>,
+}
+
+impl Solution for Day5 {
+    fn parse(input: &str) -> Result<Self> {
+        check_characters(input, |c| "FBLR".contains(c), "one of 'F', 'B', 'L' or 'R'")?;
 
-    let seat_ids = inputs
-        .lines()
-        .map(|line| convert_boarding_pass_to_binary(line))
-        .filter_map(|line| convert_from_binary(&line).ok());
+        let seat_ids = input
+            .lines()
+            .map(|line| {
+                convert_from_binary(&convert_boarding_pass_to_binary(line)).map_err(|_| {
+                    Error::at(
+                        input,
+                        line,
+                        format!("expected a boarding pass, found '{}'", line),
+                    )
+                })
+            })
+            .collect::<Result<Vec<usize>>>()?;
+        Ok(Day5 { seat_ids })
+    }
 
-    let part_one = seat_ids.clone().max().unwrap();
-    let part_two = find_gap(&seat_ids.collect::<Vec<usize>>()).unwrap();
+    fn part_one(&self) -> Option<String> {
+        self.seat_ids
+            .iter()
+            .max()
+            .map(|seat_id| seat_id.to_string())
+    }
 
-    (Some(part_one.to_string()), Some(part_two.to_string()))
+    fn part_two(&self) -> Option<String> {
+        find_gap(&self.seat_ids).map(|seat_id| seat_id.to_string())
+    }
 }
 
 fn convert_boarding_pass_to_binary(line: &str) -> String {
@@ -22,7 +42,7 @@ fn convert_boarding_pass_to_binary(line: &str) -> String {
         .replace("R", "1")
 }
 
-fn convert_from_binary(line: &str) -> Result<usize, std::num::ParseIntError> {
+fn convert_from_binary(line: &str) -> std::result::Result<usize, std::num::ParseIntError> {
     usize::from_str_radix(line, 2)
 }
 
@@ -46,13 +66,26 @@ fn find_gap(list: &[usize]) -> Option<usize> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                3,
+                "expected one of 'F', 'B', 'L' or 'R', found 'X'"
+            )),
+            Day5::parse("FBFBBFFRLR\nFBXBBFFRLR").err()
+        );
+        assert!(Day5::parse("FBFBBFFRLR\n").is_ok());
+    }
+
     #[test]
     fn test_convert_boarding_pass_to_binary() {
         assert_eq!("0101100", convert_boarding_pass_to_binary("FBFBBFF"));
     }
 
     #[test]
-    fn test_convert_from_binary() -> Result<(), std::num::ParseIntError> {
+    fn test_convert_from_binary() -> std::result::Result<(), std::num::ParseIntError> {
         assert_eq!(4, convert_from_binary("0100")?);
         assert_eq!(44, convert_from_binary("0101100")?);
         Ok(())
@@ -1,23 +1,44 @@
 use super::lib::*;
 use std::collections::HashSet;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day6.txt";
-    let inputs = read_inputs(&filename);
-    let group_questions = inputs.split_by_blank_lines();
+pub struct Day6 {
+    group_questions: Vec<String>,
+}
 
-    let part_one: usize = group_questions
-        .clone()
-        .map(get_unique_group_answers)
-        .map(|group_answers| group_answers.len())
-        .sum();
+impl Solution for Day6 {
+    fn parse(input: &str) -> Result<Self> {
+        check_characters(
+            input,
+            |c| c.is_ascii_lowercase(),
+            "an answer from 'a' to 'z'",
+        )?;
 
-    let part_two: usize = group_questions
-        .map(get_unanimous_answers)
-        .map(|group_answers| group_answers.len())
-        .sum();
+        let group_questions = input
+            .split_by_blank_lines()
+            .map(|group| group.to_string())
+            .collect();
+        Ok(Day6 { group_questions })
+    }
 
-    (Some(part_one.to_string()), Some(part_two.to_string()))
+    fn part_one(&self) -> Option<String> {
+        let part_one: usize = self
+            .group_questions
+            .iter()
+            .map(|group| get_unique_group_answers(group))
+            .map(|group_answers| group_answers.len())
+            .sum();
+        Some(part_one.to_string())
+    }
+
+    fn part_two(&self) -> Option<String> {
+        let part_two: usize = self
+            .group_questions
+            .iter()
+            .map(|group| get_unanimous_answers(group))
+            .map(|group_answers| group_answers.len())
+            .sum();
+        Some(part_two.to_string())
+    }
 }
 
 fn get_unique_group_answers(group_answers: &str) -> HashSet<char> {
@@ -45,6 +66,18 @@ fn get_unanimous_answers(group_answers: &str) -> HashSet<char> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                3,
+                2,
+                "expected an answer from 'a' to 'z', found 'B'"
+            )),
+            Day6::parse("abc\n\naBc").err()
+        );
+    }
+
     #[test]
     fn test_get_unique_group_answers() {
         let answer1 = "abcabc";
@@ -7,27 +7,43 @@ use self::petgraph::graph::{DiGraph, Graph, NodeIndex};
 use self::petgraph::graphmap::DiGraphMap;
 use self::petgraph::visit::EdgeRef;
 
+const SHINY_GOLD: &str = "shiny gold";
+
 lazy_static! {
     static ref RE_TO: Regex = Regex::new(r"(?P<to_bag>\w+ \w+) bags contain").unwrap();
     static ref RE_FROM: Regex =
         Regex::new(r"(?P<quantity>\d+) (?P<from_bag>\w+ \w+) bags?").unwrap();
 }
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day7.txt";
-    let inputs = read_inputs(&filename);
-
-    let mut graph = build_graph_from_inputs(&inputs);
-    let start_node = graph
-        .find_by_weight("shiny gold")
-        .expect("Expected to find a Shiny Gold node");
+pub struct Day7 {
+    graph: DiGraph<String, u32>,
+}
 
-    let part_one = Some(find_number_of_downstream_nodes(&graph, &start_node).to_string());
+impl Solution for Day7 {
+    fn parse(input: &str) -> Result<Self> {
+        if let Some(line) = input.lines().find(|line| !RE_TO.is_match(line)) {
+            return Err(Error::at(
+                input,
+                line,
+                format!("expected '<color> bags contain ...', found '{}'", line),
+            ));
+        }
+
+        let graph = build_graph_from_inputs(input).map(|_, node| node.to_string(), |_, edge| *edge);
+        Ok(Day7 { graph })
+    }
 
-    graph.reverse();
-    let part_two = Some(find_number_of_contained_nodes(&graph, &start_node).to_string());
+    fn part_one(&self) -> Option<String> {
+        let start_node = self.graph.find_by_weight(SHINY_GOLD.to_string())?;
+        Some(find_number_of_downstream_nodes(&self.graph, &start_node).to_string())
+    }
 
-    (part_one, part_two)
+    fn part_two(&self) -> Option<String> {
+        let mut graph = self.graph.clone();
+        graph.reverse();
+        let start_node = graph.find_by_weight(SHINY_GOLD.to_string())?;
+        Some(find_number_of_contained_nodes(&graph, &start_node).to_string())
+    }
 }
 
 trait FindByWeight<N: Eq> {
@@ -56,7 +72,9 @@ fn build_graph_from_inputs(inputs: &str) -> DiGraph<&str, u32> {
 }
 
 fn parse_input_into_from_to(input: &str) -> Option<(Vec<(u32, &str)>, &str)> {
-    let to = RE_TO.captures(input).unwrap().name("to_bag");
+    let to = RE_TO
+        .captures(input)
+        .and_then(|captures| captures.name("to_bag"));
     let from: Vec<(u32, &str)> = RE_FROM
         .captures_iter(input)
         .filter_map(|x| match (x.name("quantity"), x.name("from_bag")) {
@@ -72,17 +90,17 @@ fn parse_input_into_from_to(input: &str) -> Option<(Vec<(u32, &str)>, &str)> {
     }
 }
 
-fn find_number_of_downstream_nodes(graph: &DiGraph<&str, u32>, start_node: &NodeIndex) -> u32 {
-    let mut bfs = petgraph::visit::Bfs::new(&graph, *start_node);
+fn find_number_of_downstream_nodes<N>(graph: &DiGraph<N, u32>, start_node: &NodeIndex) -> u32 {
+    let mut bfs = petgraph::visit::Bfs::new(graph, *start_node);
 
     let mut node_count = 0;
-    while bfs.next(&graph).is_some() {
+    while bfs.next(graph).is_some() {
         node_count += 1;
     }
     node_count - 1
 }
 
-fn find_number_of_contained_nodes(graph: &DiGraph<&str, u32>, start_node: &NodeIndex) -> u32 {
+fn find_number_of_contained_nodes<N>(graph: &DiGraph<N, u32>, start_node: &NodeIndex) -> u32 {
     graph
         .edges(*start_node)
         .map(|edge| edge.weight() * (find_number_of_contained_nodes(graph, &edge.target()) + 1))
@@ -112,6 +130,19 @@ dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags.";
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                1,
+                "expected '<color> bags contain ...', found 'shiny gold holds 2 dark red bags.'"
+            )),
+            Day7::parse("dark red bags contain no other bags.\nshiny gold holds 2 dark red bags.")
+                .err()
+        );
+    }
+
     #[test]
     fn test_parse_graph() {
         let mut graph = DiGraphMap::<&str, u32>::new();
@@ -137,9 +168,9 @@ dark violet bags contain no other bags.";
         graph.add_edge("dotted black", "vibrant plum", 0);
 
         let graph = graph.into_graph::<u32>();
-        let input_graph = build_graph_from_inputs(&TEST_INPUT_1);
+        let input_graph = build_graph_from_inputs(TEST_INPUT_1);
 
-        assert_eq!(true, is_isomorphic(&graph, &input_graph));
+        assert!(is_isomorphic(&graph, &input_graph));
     }
 
     #[test]
@@ -148,26 +179,26 @@ dark violet bags contain no other bags.";
         let expected_output = Some((vec![(1, "bright white"), (2, "muted yellow")], "light red"));
         let empty_input = "faded blue bags contain no other bags.";
 
-        assert_eq!(expected_output, parse_input_into_from_to(&input));
-        assert_eq!(None, parse_input_into_from_to(&empty_input));
+        assert_eq!(expected_output, parse_input_into_from_to(input));
+        assert_eq!(None, parse_input_into_from_to(empty_input));
     }
 
     #[test]
     fn test_find_number_of_downstream_nodes() {
-        let graphmap = build_graph_from_inputs(&TEST_INPUT_1);
+        let graphmap = build_graph_from_inputs(TEST_INPUT_1);
         let start_node = graphmap.find_by_weight("shiny gold").unwrap();
         assert_eq!(4, find_number_of_downstream_nodes(&graphmap, &start_node));
     }
 
     #[test]
     fn test_find_number_of_contained_nodes() {
-        let mut graphmap = build_graph_from_inputs(&TEST_INPUT_1);
+        let mut graphmap = build_graph_from_inputs(TEST_INPUT_1);
         graphmap.reverse();
 
         let start_node = graphmap.find_by_weight("shiny gold").unwrap();
         assert_eq!(32, find_number_of_contained_nodes(&graphmap, &start_node));
 
-        let mut graphmap = build_graph_from_inputs(&TEST_INPUT_2);
+        let mut graphmap = build_graph_from_inputs(TEST_INPUT_2);
         graphmap.reverse();
 
         let start_node = graphmap.find_by_weight("shiny gold").unwrap();
@@ -1,192 +1,69 @@
+use super::lib::handheld::{parse_program, repairs, Console, Halt, Instruction, State};
 use super::lib::*;
 
-use std::collections::HashSet;
-
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day8.txt";
-    let inputs = read_inputs(&filename);
-    let program = parse_program(&inputs);
-
-    let part_one = Some(
-        run_program_and_stop_on_first_repeated(&program)
-            .accumulator
-            .to_string(),
-    );
-    let part_two = Some(fix_and_run_program(&program).accumulator.to_string());
-
-    (part_one, part_two)
+pub struct Day8 {
+    program: Vec<Instruction>,
 }
 
-fn parse_program(program: &str) -> Vec<Instruction> {
-    program
-        .lines()
-        .map(|line| line.split_whitespace())
-        .map(|mut line| Instruction {
-            operation: Operation::from_str(line.next().expect("Expected an operation"))
-                .expect("Expected an operation"),
-            argument: line
-                .next()
-                .expect("Expected argument")
-                .parse::<i64>()
-                .expect("Expected an integer"),
+impl Solution for Day8 {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Day8 {
+            program: parse_program(input)?,
         })
-        .collect()
-}
-
-fn run_program_and_stop_on_first_repeated(program: &[Instruction]) -> State {
-    let mut state = State::EMPTY;
-    let mut instruction_history = HashSet::new();
-
-    loop {
-        let instruction = program.get(state.program_counter);
-
-        if instruction.is_none() {
-            break state;
-        }
-
-        let instruction = program.get(state.program_counter).unwrap();
-        state = run_instruction(instruction, &state);
-
-        if instruction_history.contains(&state.program_counter) {
-            break state;
-        }
-        instruction_history.insert(state.program_counter);
     }
-}
 
-fn run_instruction(instruction: &Instruction, state: &State) -> State {
-    match instruction.operation {
-        Operation::NOP => State {
-            program_counter: state.program_counter + 1,
-            ..*state
-        },
-        Operation::JMP => State {
-            program_counter: (state.program_counter as i64 + instruction.argument) as usize,
-            ..*state
-        },
-        Operation::ACC => State {
-            program_counter: state.program_counter + 1,
-            accumulator: state.accumulator + instruction.argument,
-        },
+    fn part_one(&self) -> Option<String> {
+        Some(
+            run_program_and_stop_on_first_repeated(&self.program)
+                .accumulator
+                .to_string(),
+        )
     }
-}
 
-fn fix_and_run_program(program: &[Instruction]) -> State {
-    let mut starting_idx = 0;
-
-    loop {
-        let mut program = program.to_vec();
-        let position = program
-            .iter()
-            .skip(starting_idx)
-            .position(|instruction| {
-                matches!(instruction.operation, Operation::NOP | Operation::JMP)
-            })
-            .unwrap();
-
-        let mut instruction = program.get_mut(starting_idx + position).unwrap();
-        starting_idx += position + 1;
-        instruction.operation = instruction.operation.opposite_instruction();
-        let state = run_program_and_stop_on_first_repeated(&program);
-        if state.program_counter == program.len() {
-            break state;
-        }
+    fn part_two(&self) -> Option<String> {
+        fix_and_run_program(&self.program).map(|state| state.accumulator.to_string())
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct State {
-    accumulator: i64,
-    program_counter: usize,
-}
-
-impl State {
-    const EMPTY: State = State {
-        program_counter: 0,
-        accumulator: 0,
-    };
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Operation {
-    NOP,
-    ACC,
-    JMP,
+fn run_program_and_stop_on_first_repeated(program: &[Instruction]) -> State {
+    let mut console = Console::new(program);
+    console.run();
+    console.state()
 }
 
-impl Operation {
-    fn from_str(operation: &str) -> Option<Operation> {
-        match operation {
-            "nop" => Some(Operation::NOP),
-            "acc" => Some(Operation::ACC),
-            "jmp" => Some(Operation::JMP),
-            _ => None,
-        }
-    }
+/// Swaps the first `jmp` or `nop` that makes the program terminate, and returns its final state.
+fn fix_and_run_program(program: &[Instruction]) -> Option<State> {
+    let idx = *repairs(program).first()?;
+    let mut program = program.to_vec();
+    program[idx] = program[idx].flipped()?;
 
-    fn opposite_instruction(&self) -> Self {
-        match self {
-            Operation::NOP => Operation::JMP,
-            Operation::JMP => Operation::NOP,
-            _ => *self,
-        }
+    let mut console = Console::new(&program);
+    match console.run() {
+        Halt::Terminated => Some(console.state()),
+        _ => None,
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-struct Instruction {
-    operation: Operation,
-    argument: i64,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const TEST_CASE: &str = "nop +0
-acc +1
-jmp +4
-acc +3
-jmp -3
-acc -99
-acc +1
-jmp -4
-acc +6";
-
-    #[test]
-    fn test_parse_program() {
-        let program = "nop +0
-acc +1
-jmp +4";
-
-        let expected_program = vec![
-            Instruction {
-                operation: Operation::NOP,
-                argument: 0,
-            },
-            Instruction {
-                operation: Operation::ACC,
-                argument: 1,
-            },
-            Instruction {
-                operation: Operation::JMP,
-                argument: 4,
-            },
-        ];
-        assert_eq!(expected_program, parse_program(program));
-    }
+    use days::lib::handheld::TEST_CASE;
 
     #[test]
-    fn test_operation_from_str() {
-        assert_eq!(Some(Operation::NOP), Operation::from_str("nop"));
-        assert_eq!(Some(Operation::ACC), Operation::from_str("acc"));
-        assert_eq!(Some(Operation::JMP), Operation::from_str("jmp"));
-        assert_eq!(None, Operation::from_str("abc"));
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                1,
+                "expected 'nop', 'acc' or 'jmp', found 'add'"
+            )),
+            Day8::parse("nop +0\nadd +1").err()
+        );
     }
 
     #[test]
     fn test_run_program_and_stop_on_first_repeated() {
-        let program = parse_program(&TEST_CASE);
+        let program = parse_program(TEST_CASE).unwrap();
         assert_eq!(
             5,
             run_program_and_stop_on_first_repeated(&program).accumulator
@@ -195,54 +72,22 @@ jmp +4";
 
     #[test]
     fn test_fix_and_run_program() {
-        let program = parse_program(&TEST_CASE);
-        assert_eq!(8, fix_and_run_program(&program).accumulator);
-    }
-
-    #[test]
-    fn test_run_instruction() {
-        let initial_state = State {
-            accumulator: 10,
-            program_counter: 10,
-        };
+        let program = parse_program(TEST_CASE).unwrap();
         assert_eq!(
-            State {
-                program_counter: initial_state.program_counter + 1,
-                ..initial_state
-            },
-            run_instruction(
-                &Instruction {
-                    operation: Operation::NOP,
-                    argument: 0
-                },
-                &initial_state
-            )
+            Some(8),
+            fix_and_run_program(&program).map(|state| state.accumulator)
         );
+        let program = parse_program("jmp +0\njmp +0").unwrap();
+        assert_eq!(None, fix_and_run_program(&program));
+        let program = parse_program("jmp +5\nacc +1").unwrap();
         assert_eq!(
-            State {
-                program_counter: initial_state.program_counter + 10,
-                ..initial_state
-            },
-            run_instruction(
-                &Instruction {
-                    operation: Operation::JMP,
-                    argument: 10
-                },
-                &initial_state,
-            )
+            Some(1),
+            fix_and_run_program(&program).map(|state| state.accumulator)
         );
+        let program = parse_program("jmp +9223372036854775807").unwrap();
         assert_eq!(
-            State {
-                program_counter: initial_state.program_counter + 1,
-                accumulator: initial_state.accumulator + 10,
-            },
-            run_instruction(
-                &Instruction {
-                    operation: Operation::ACC,
-                    argument: 10
-                },
-                &initial_state
-            )
+            Some(0),
+            fix_and_run_program(&program).map(|state| state.accumulator)
         );
     }
 }
@@ -6,25 +6,44 @@ use std::collections::HashSet;
 extern crate itertools;
 use self::itertools::Itertools;
 
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day9.txt";
-    let inputs = read_inputs(&filename);
-    let inputs: Vec<usize> = inputs
-        .lines_of::<usize>()
-        .iter()
-        .filter_map(|x| *x)
-        .collect();
+const LOOK_BEHIND: usize = 25;
+
+pub struct Day9 {
+    numbers: Vec<usize>,
+}
+
+impl Solution for Day9 {
+    fn parse(input: &str) -> Result<Self> {
+        let numbers: Vec<usize> = parse_lines(input, "a positive integer")?;
+        if numbers.len() <= LOOK_BEHIND {
+            return Err(Error::input(format!(
+                "expected more than {} numbers, found {}",
+                LOOK_BEHIND,
+                numbers.len()
+            )));
+        }
+        Ok(Day9 { numbers })
+    }
 
-    let part_one_solution = find_first_number_not_sum_of_two_previous(&inputs, 25);
-    let part_one = Some(part_one_solution.to_string());
-    let part_two_solution = find_continuous_list_that_sums_to_number(&inputs, &part_one_solution);
-    let part_two_solution = part_two_solution.iter().minmax().into_option().unwrap();
-    let part_two = Some((part_two_solution.0 + part_two_solution.1).to_string());
+    fn part_one(&self) -> Option<String> {
+        find_first_number_not_sum_of_two_previous(&self.numbers, LOOK_BEHIND)
+            .map(|number| number.to_string())
+    }
 
-    (part_one, part_two)
+    fn part_two(&self) -> Option<String> {
+        let invalid_number = find_first_number_not_sum_of_two_previous(&self.numbers, LOOK_BEHIND)?;
+        let contiguous_list =
+            find_continuous_list_that_sums_to_number(&self.numbers, &invalid_number)?;
+        let (min, max) = contiguous_list.iter().minmax().into_option()?;
+        Some((min + max).to_string())
+    }
 }
 
-fn find_first_number_not_sum_of_two_previous(inputs: &[usize], look_behind: usize) -> usize {
+/// The first number that isn't the sum of two of the `look_behind` numbers before it, if any.
+fn find_first_number_not_sum_of_two_previous(
+    inputs: &[usize],
+    look_behind: usize,
+) -> Option<usize> {
     let result = inputs
         .windows(look_behind + 1)
         .map(|window| {
@@ -40,38 +59,39 @@ fn find_first_number_not_sum_of_two_previous(inputs: &[usize], look_behind: usiz
         })
         .find(|(next_number, previous_numbers)| {
             !is_number_sum_of_two_others(next_number, previous_numbers)
-        })
-        .unwrap();
-    *result.0
+        })?;
+    Some(*result.0)
 }
 
 fn is_number_sum_of_two_others(number: &usize, previous_numbers: &HashSet<usize>) -> bool {
     previous_numbers
         .iter()
         .filter(|previous_number| *previous_number <= number)
-        .map(|previous_number| previous_numbers.contains(&((*number) - previous_number)))
-        .any(|x| x)
+        .any(|previous_number| previous_numbers.contains(&((*number) - previous_number)))
 }
 
-fn find_continuous_list_that_sums_to_number(inputs: &[usize], number: &usize) -> Vec<usize> {
+/// The first run of at least two numbers that adds up to `number`, if there is one.
+fn find_continuous_list_that_sums_to_number(
+    inputs: &[usize],
+    number: &usize,
+) -> Option<Vec<usize>> {
     let mut tail_idx = 0;
     let mut head_idx = 1;
-    let mut sum: usize = inputs.get(tail_idx..=head_idx).unwrap().iter().sum();
+    let mut sum: usize = inputs.get(tail_idx..=head_idx)?.iter().sum();
 
     loop {
         match sum.cmp(number) {
-            Ordering::Greater => {
-                sum -= inputs.get(tail_idx).unwrap();
+            Ordering::Greater if head_idx > tail_idx + 1 => {
+                sum -= inputs[tail_idx];
                 tail_idx += 1;
             }
-            Ordering::Less => {
+            Ordering::Equal => return Some(inputs[tail_idx..=head_idx].to_vec()),
+            _ => {
                 head_idx += 1;
-                sum += inputs.get(head_idx).unwrap();
+                sum = sum.checked_add(*inputs.get(head_idx)?)?;
             }
-            Ordering::Equal => break,
         }
     }
-    inputs.get(tail_idx..=head_idx).unwrap().to_vec()
 }
 
 #[cfg(test)]
@@ -82,16 +102,50 @@ mod tests {
         35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576,
     ];
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Some(Error::parse(
+                2,
+                1,
+                "expected a positive integer, found '-20'"
+            )),
+            Day9::parse("35\n-20").err()
+        );
+        assert_eq!(
+            Some(Error::input("expected more than 25 numbers, found 2")),
+            Day9::parse("35\n20").err()
+        );
+    }
+
     #[test]
     fn test_find_first_number_not_sum_of_two_previous() {
-        assert_eq!(127, find_first_number_not_sum_of_two_previous(&NUMBERS, 5));
+        assert_eq!(
+            Some(127),
+            find_first_number_not_sum_of_two_previous(&NUMBERS, 5)
+        );
+        assert_eq!(
+            None,
+            find_first_number_not_sum_of_two_previous(&NUMBERS[..14], 5)
+        );
     }
 
     #[test]
     fn test_find_continuous_list_that_sums_to_number() {
         assert_eq!(
-            [15, 25, 47, 40].to_vec(),
+            Some([15, 25, 47, 40].to_vec()),
             find_continuous_list_that_sums_to_number(&NUMBERS, &127),
         );
+        assert_eq!(None, find_continuous_list_that_sums_to_number(&NUMBERS, &1));
+        assert_eq!(None, find_continuous_list_that_sums_to_number(&[5], &5));
+    }
+
+    #[test]
+    fn test_no_invalid_number() {
+        let input: Vec<String> = (1..=30).map(|number| number.to_string()).collect();
+        let day = Day9::parse(&input.join("\n")).unwrap();
+
+        assert_eq!(None, day.part_one());
+        assert_eq!(None, day.part_two());
     }
 }
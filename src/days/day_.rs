@@ -1,16 +0,0 @@
-use super::lib::*;
-
-pub fn run() -> (Option<String>, Option<String>) {
-    let filename = "inputs/day__.txt";
-    let inputs = read_inputs(&filename);
-
-    let part_one = None;
-    let part_two = None;
-
-    (part_one, part_two)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-}
@@ -1,8 +1,111 @@
 #![warn(clippy::all)]
-use std::fs;
+pub mod assembly;
+pub mod automaton;
+pub mod control_flow;
+pub mod dense;
+pub mod expression;
+pub mod grammar;
+pub mod grid;
+pub mod handheld;
+pub mod input;
+pub mod pattern;
+pub mod recorder;
+pub mod repair;
 
-pub fn read_inputs(filename: &str) -> String {
-    fs::read_to_string(filename).expect("Couldn't read file")
+pub use self::grid::Grid;
+pub use self::input::{read_inputs, InputKind, InputProvider, InputSource};
+pub use self::recorder::{Palette, Recorder};
+pub use error::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(self) -> usize {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+/// Writes the part the way answers are labelled: "one" or "two".
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "one"),
+            Part::Two => write!(f, "two"),
+        }
+    }
+}
+
+/// A day's puzzle, split into parsing the input and solving each of its parts.
+pub trait Solution {
+    fn parse(input: &str) -> Result<Self>
+    where
+        Self: Sized;
+
+    fn part_one(&self) -> Option<String>;
+
+    fn part_two(&self) -> Option<String>;
+
+    /// Like `part_one`, for days that can find out while solving that the input is wrong, such
+    /// as an answer that overflows. This is what the runner calls.
+    fn try_part_one(&self) -> Result<Option<String>> {
+        Ok(self.part_one())
+    }
+
+    /// Like `part_two`, the way `try_part_one` is like `part_one`.
+    fn try_part_two(&self) -> Result<Option<String>> {
+        Ok(self.part_two())
+    }
+
+    /// The frames of the simulation behind `part`, for days that have one.
+    fn animate(&self, _part: Part) -> Option<Recorder> {
+        None
+    }
+}
+
+/// Parses `token`, a slice of `input`, pointing at it if it isn't a valid `T`.
+pub fn parse_token<T: FromStr>(input: &str, token: &str, expected: &str) -> Result<T> {
+    token.parse::<T>().map_err(|_| {
+        Error::at(
+            input,
+            token,
+            format!("expected {}, found '{}'", expected, token),
+        )
+    })
+}
+
+/// Fails on the first character of `input`, other than a line break, that isn't allowed.
+pub fn check_characters<F: Fn(char) -> bool>(
+    input: &str,
+    is_allowed: F,
+    expected: &str,
+) -> Result<()> {
+    match input
+        .char_indices()
+        .find(|(_, c)| *c != '\n' && !is_allowed(*c))
+    {
+        Some((idx, c)) => Err(Error::at(
+            input,
+            &input[idx..],
+            format!("expected {}, found '{}'", expected, c),
+        )),
+        None => Ok(()),
+    }
+}
+
+pub fn parse_lines<T: FromStr>(input: &str, expected: &str) -> Result<Vec<T>> {
+    input
+        .lines()
+        .map(|line| parse_token(input, line.trim(), expected))
+        .collect()
 }
 
 pub trait LinesOf {
@@ -16,11 +119,11 @@ impl LinesOf for str {
 }
 
 pub trait SplitByBlankLines {
-    fn split_by_blank_lines(&self) -> std::str::Split<&str>;
+    fn split_by_blank_lines(&self) -> std::str::Split<'_, &str>;
 }
 
 impl SplitByBlankLines for str {
-    fn split_by_blank_lines(&self) -> std::str::Split<&str> {
+    fn split_by_blank_lines(&self) -> std::str::Split<'_, &str> {
         self.split("\n\n")
     }
 }
@@ -48,6 +151,48 @@ a";
         );
     }
 
+    #[test]
+    fn test_part() {
+        assert_eq!(2, Part::Two.number());
+        assert_eq!("one", Part::One.to_string());
+        assert_eq!("Part two", format!("Part {}", Part::Two));
+    }
+
+    #[test]
+    fn test_parse_token() {
+        let input = "12 ab";
+        assert_eq!(
+            Ok(12),
+            parse_token::<usize>(input, &input[0..2], "a number")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 4, "expected a number, found 'ab'")),
+            parse_token::<usize>(input, &input[3..], "a number")
+        );
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(
+            Ok(vec![1, 2, 3]),
+            parse_lines::<i64>("1\n2\n3", "an integer")
+        );
+        assert_eq!(
+            Err(Error::parse(3, 1, "expected an integer, found 'a'")),
+            parse_lines::<i64>("1\n2\na", "an integer")
+        );
+    }
+
+    #[test]
+    fn test_check_characters() {
+        let is_allowed = |c| c == '.' || c == '#';
+        assert_eq!(Ok(()), check_characters(".#\n#.", is_allowed, "'.' or '#'"));
+        assert_eq!(
+            Err(Error::parse(2, 2, "expected '.' or '#', found 'x'")),
+            check_characters(".#\n#x", is_allowed, "'.' or '#'")
+        );
+    }
+
     #[test]
     fn test_split_by_blank_lines() {
         let input = "ab
@@ -0,0 +1,232 @@
+use super::handheld::Instruction;
+use super::parse_token;
+use error::{Error, Result};
+use std::collections::HashMap;
+
+/// Writes a program out with labels, `L1`, `L2`, ..., in place of the offsets of the jumps that
+/// stay within it. Labels go on a line of their own, and instructions are indented under them.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let len = program.len();
+    let mut targets: Vec<usize> = program
+        .iter()
+        .enumerate()
+        .filter(|(_, instruction)| matches!(instruction, Instruction::Jmp(_)))
+        .filter_map(|(program_counter, instruction)| instruction.successor(program_counter, len))
+        .collect();
+    targets.sort_unstable();
+    targets.dedup();
+    let labels: HashMap<usize, String> = targets
+        .iter()
+        .enumerate()
+        .map(|(idx, target)| (*target, format!("L{}", idx + 1)))
+        .collect();
+
+    let mut lines = Vec::new();
+    for program_counter in 0..=len {
+        if let Some(label) = labels.get(&program_counter) {
+            lines.push(format!("{}:", label));
+        }
+        let instruction = match program.get(program_counter) {
+            Some(instruction) => instruction,
+            None => break,
+        };
+        let label = match instruction {
+            Instruction::Jmp(_) => instruction
+                .successor(program_counter, len)
+                .and_then(|target| labels.get(&target)),
+            _ => None,
+        };
+        lines.push(match label {
+            Some(label) => format!("    {} {}", instruction.mnemonic(), label),
+            None => format!("    {}", instruction),
+        });
+    }
+    lines.join("\n")
+}
+
+/// Reads a program the way `disassemble` writes it. Blank lines are skipped, and the argument
+/// of a `jmp` or `nop` can be a label as well as an offset.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match line.strip_suffix(':') {
+            Some(label) => {
+                if !is_label(label) {
+                    return Err(Error::at(
+                        source,
+                        line,
+                        format!("expected a label, found '{}'", label),
+                    ));
+                }
+                if labels.insert(label, statements.len()).is_some() {
+                    return Err(Error::at(
+                        source,
+                        line,
+                        format!("label '{}' is defined twice", label),
+                    ));
+                }
+            }
+            None => statements.push(line),
+        }
+    }
+
+    statements
+        .iter()
+        .enumerate()
+        .map(|(program_counter, line)| {
+            let mut tokens = line.split_whitespace();
+            let mnemonic = tokens.next().unwrap();
+            let token = match tokens.next() {
+                Some(token) => token,
+                None => return Err(Error::at(source, line, "expected an argument")),
+            };
+            if let Some(token) = tokens.next() {
+                return Err(Error::at(
+                    source,
+                    token,
+                    format!("expected the end of the line, found '{}'", token),
+                ));
+            }
+
+            let argument = if mnemonic == "acc" || !is_label(token) {
+                parse_token(source, token, "an integer argument")?
+            } else {
+                match labels.get(token) {
+                    Some(target) => *target as i64 - program_counter as i64,
+                    None => {
+                        return Err(Error::at(
+                            source,
+                            token,
+                            format!("unknown label '{}'", token),
+                        ))
+                    }
+                }
+            };
+
+            Instruction::new(mnemonic, argument).ok_or_else(|| {
+                Error::at(
+                    source,
+                    mnemonic,
+                    format!("expected 'nop', 'acc' or 'jmp', found '{}'", mnemonic),
+                )
+            })
+        })
+        .collect()
+}
+
+fn is_label(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use days::lib::handheld::{parse_program, TEST_CASE};
+
+    const DISASSEMBLED: &str = "    nop +0
+L1:
+    acc +1
+    jmp L3
+L2:
+    acc +3
+    jmp L1
+    acc -99
+L3:
+    acc +1
+    jmp L2
+    acc +6";
+
+    #[test]
+    fn test_disassemble() {
+        let program = parse_program(TEST_CASE).unwrap();
+        assert_eq!(DISASSEMBLED, disassemble(&program));
+
+        let program = parse_program("jmp +2\njmp -5\nnop +1").unwrap();
+        assert_eq!(
+            "    jmp L1\n    jmp -5\nL1:\n    nop +1",
+            disassemble(&program)
+        );
+        let program = parse_program("jmp +1").unwrap();
+        assert_eq!("    jmp L1\nL1:", disassemble(&program));
+    }
+
+    #[test]
+    fn test_assemble() {
+        let program = parse_program(TEST_CASE).unwrap();
+        assert_eq!(Ok(program.clone()), assemble(DISASSEMBLED));
+        assert_eq!(Ok(program), assemble(TEST_CASE));
+
+        assert_eq!(
+            Ok(vec![
+                Instruction::Nop(3),
+                Instruction::Jmp(-1),
+                Instruction::Acc(-2),
+            ]),
+            assemble("start:\n  nop end\n  jmp start\n\n  acc -2\nend:")
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for program in &[
+            TEST_CASE,
+            "jmp +0",
+            "jmp +3\nacc +1\njmp -1",
+            "jmp -5\nnop +0",
+        ] {
+            let program = parse_program(program).unwrap();
+            assert_eq!(Ok(program.clone()), assemble(&disassemble(&program)));
+        }
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(
+            Err(Error::parse(2, 5, "unknown label 'L2'")),
+            assemble("L1:\njmp L2")
+        );
+        assert_eq!(
+            Err(Error::parse(3, 1, "label 'L1' is defined twice")),
+            assemble("L1:\nnop +0\nL1:")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 1, "expected a label, found '1a'")),
+            assemble("1a:")
+        );
+        assert_eq!(
+            Err(Error::parse(
+                2,
+                5,
+                "expected an integer argument, found 'L1'"
+            )),
+            assemble("L1:\nacc L1")
+        );
+        assert_eq!(
+            Err(Error::parse(
+                1,
+                1,
+                "expected 'nop', 'acc' or 'jmp', found 'add'"
+            )),
+            assemble("add +1")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 3, "expected an argument")),
+            assemble("  jmp")
+        );
+        assert_eq!(
+            Err(Error::parse(
+                2,
+                12,
+                "expected the end of the line, found 'extra'"
+            )),
+            assemble("L1:\n    jmp L1 extra")
+        );
+    }
+}
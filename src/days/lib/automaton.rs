@@ -0,0 +1,455 @@
+use super::grid::Grid;
+use super::parse_token;
+use error::{Error, Result};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// A cell of a `D` dimensional space.
+pub type Point<const D: usize> = [i32; D];
+
+/// The offsets of the `3^D - 1` neighbours of a cell.
+pub fn neighbour_deltas<const D: usize>() -> Vec<Point<D>> {
+    (0..3usize.pow(D as u32))
+        .map(|idx| {
+            let mut delta = [0; D];
+            let mut digits = idx;
+            for axis in delta.iter_mut() {
+                *axis = (digits % 3) as i32 - 1;
+                digits /= 3;
+            }
+            delta
+        })
+        .filter(|delta| delta.iter().any(|axis| *axis != 0))
+        .collect()
+}
+
+/// A Life-like rule: the neighbour counts for which a dead cell comes alive, and those for
+/// which a live cell stays alive.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rule {
+    birth: BTreeSet<usize>,
+    survival: BTreeSet<usize>,
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub fn conway() -> Self {
+        Rule {
+            birth: [3].iter().copied().collect(),
+            survival: [2, 3].iter().copied().collect(),
+        }
+    }
+
+    /// Parses a rule string such as `B36/S23`. Counts above 9 are written separated by commas,
+    /// as in `B3,10/S2`.
+    pub fn parse(rule: &str) -> Result<Self> {
+        let mut birth = None;
+        let mut survival = None;
+
+        for part in rule.split('/') {
+            let (counts, target) = match part.chars().next() {
+                Some('B') => (&part[1..], &mut birth),
+                Some('S') => (&part[1..], &mut survival),
+                _ => {
+                    let message = format!("expected 'B<counts>' or 'S<counts>', found '{}'", part);
+                    return Err(Error::at(rule, part, message));
+                }
+            };
+            if target.is_some() {
+                let message = format!("'{}' is given twice", &part[..1]);
+                return Err(Error::at(rule, part, message));
+            }
+            *target = Some(parse_counts(rule, counts)?);
+        }
+
+        let (birth, survival) = match (birth, survival) {
+            (Some(birth), Some(survival)) => (birth, survival),
+            _ => {
+                return Err(Error::input(
+                    "expected a rule of the form 'B<counts>/S<counts>'",
+                ))
+            }
+        };
+        if birth.contains(&0) {
+            return Err(Error::input("B0 rules would fill all of space"));
+        }
+        Ok(Rule { birth, survival })
+    }
+
+    pub fn next_state(&self, alive: bool, neighbours: usize) -> bool {
+        if alive {
+            self.survival.contains(&neighbours)
+        } else {
+            self.birth.contains(&neighbours)
+        }
+    }
+}
+
+fn parse_counts(rule: &str, counts: &str) -> Result<BTreeSet<usize>> {
+    if counts.contains(',') {
+        return counts
+            .split(',')
+            .map(|count| parse_token(rule, count, "a neighbour count"))
+            .collect();
+    }
+
+    counts
+        .char_indices()
+        .map(|(idx, c)| match c.to_digit(10) {
+            Some(count) => Ok(count as usize),
+            None => Err(Error::at(
+                rule,
+                &counts[idx..],
+                format!("expected a neighbour count, found '{}'", c),
+            )),
+        })
+        .collect()
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |counts: &BTreeSet<usize>| {
+            let separator = if counts.iter().any(|count| *count > 9) {
+                ","
+            } else {
+                ""
+            };
+            counts
+                .iter()
+                .map(|count| count.to_string())
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+    }
+}
+
+/// How a run of an automaton ended.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    /// Still changing after the last generation that was run.
+    Running,
+    /// The cells stopped changing at `generation`.
+    FixedPoint { generation: usize },
+    /// The cells of generation `start` come back every `period` generations.
+    Cycle { start: usize, period: usize },
+}
+
+/// A cellular automaton over an unbounded `D` dimensional space, storing only its live cells.
+#[derive(Debug, Clone)]
+pub struct Automaton<const D: usize> {
+    cells: HashSet<Point<D>>,
+    rule: Rule,
+    deltas: Vec<Point<D>>,
+    generation: usize,
+}
+
+impl<const D: usize> Automaton<D> {
+    pub fn new<I: IntoIterator<Item = Point<D>>>(cells: I, rule: Rule) -> Self {
+        Automaton {
+            cells: cells.into_iter().collect(),
+            rule,
+            deltas: neighbour_deltas(),
+            generation: 0,
+        }
+    }
+
+    /// Places a plane at the origin of the first two axes, with the cells for which `is_alive`
+    /// holds alive.
+    pub fn from_plane<T, F: Fn(&T) -> bool>(plane: &Grid<T>, is_alive: F, rule: Rule) -> Self {
+        assert!(D >= 2, "a plane needs at least 2 dimensions");
+        let cells = plane
+            .iter()
+            .filter(|(_, cell)| is_alive(cell))
+            .map(|((row, col), _)| {
+                let mut point = [0; D];
+                point[0] = row as i32;
+                point[1] = col as i32;
+                point
+            });
+        Automaton::new(cells, rule)
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_alive(&self, point: &Point<D>) -> bool {
+        self.cells.contains(point)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &Point<D>> {
+        self.cells.iter()
+    }
+
+    /// The smallest and largest coordinates of the live cells on each axis.
+    pub fn bounds(&self) -> Option<(Point<D>, Point<D>)> {
+        let mut cells = self.cells.iter();
+        let first = *cells.next()?;
+        Some(cells.fold((first, first), |(mut min, mut max), point| {
+            for axis in 0..D {
+                min[axis] = min[axis].min(point[axis]);
+                max[axis] = max[axis].max(point[axis]);
+            }
+            (min, max)
+        }))
+    }
+
+    /// Draws the planes of the first two axes that hold live cells, side by side along the third
+    /// axis and stacked along the fourth and later ones, with blank space between planes.
+    pub fn render(&self, alive: char, dead: char) -> Grid<char> {
+        let (min, max) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return Grid::filled(1, 1, dead),
+        };
+        let extent = |axis: usize| (max[axis] - min[axis] + 1) as usize;
+        let (height, width) = (extent(0), extent(1));
+        let across = if D > 2 { extent(2) } else { 1 };
+        let down: usize = (3..D).map(extent).product();
+
+        Grid::from_fn(
+            across * (width + 1) - 1,
+            down * (height + 1) - 1,
+            |row, col| {
+                let (layer_row, row) = (row / (height + 1), row % (height + 1));
+                let (layer_col, col) = (col / (width + 1), col % (width + 1));
+                if row == height || col == width {
+                    return ' ';
+                }
+
+                let mut point = min;
+                point[0] += row as i32;
+                point[1] += col as i32;
+                if D > 2 {
+                    point[2] += layer_col as i32;
+                }
+                let mut layer = layer_row;
+                for (axis, coordinate) in point.iter_mut().enumerate().skip(3) {
+                    *coordinate += (layer % extent(axis)) as i32;
+                    layer /= extent(axis);
+                }
+
+                if self.is_alive(&point) {
+                    alive
+                } else {
+                    dead
+                }
+            },
+        )
+    }
+
+    pub fn live_neighbours(&self, point: &Point<D>) -> usize {
+        self.deltas
+            .iter()
+            .filter(|delta| self.cells.contains(&add(point, delta)))
+            .count()
+    }
+
+    /// Whether `point` is alive in the next generation.
+    pub fn next_state(&self, point: &Point<D>) -> bool {
+        self.rule
+            .next_state(self.is_alive(point), self.live_neighbours(point))
+    }
+
+    pub fn step(&mut self) {
+        let mut neighbour_counts: HashMap<Point<D>, usize> = HashMap::new();
+        for point in &self.cells {
+            for delta in &self.deltas {
+                *neighbour_counts.entry(add(point, delta)).or_insert(0) += 1;
+            }
+        }
+
+        // Live cells without any live neighbour aren't counted, but may survive with S0.
+        let lonely_cells = self
+            .cells
+            .iter()
+            .filter(|point| !neighbour_counts.contains_key(*point))
+            .filter(|_| self.rule.next_state(true, 0));
+        let cells = neighbour_counts
+            .iter()
+            .filter(|(point, count)| self.rule.next_state(self.cells.contains(*point), **count))
+            .map(|(point, _)| point)
+            .chain(lonely_cells)
+            .copied()
+            .collect();
+
+        self.cells = cells;
+        self.generation += 1;
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    /// Runs until the cells repeat an earlier generation, or for at most `max_generations`.
+    pub fn run_until_repeat(&mut self, max_generations: usize) -> Outcome {
+        let mut seen = HashMap::new();
+        seen.insert(self.snapshot(), self.generation);
+
+        for _ in 0..max_generations {
+            self.step();
+            match seen.entry(self.snapshot()) {
+                Entry::Occupied(entry) => {
+                    let start = *entry.get();
+                    return match self.generation - start {
+                        1 => Outcome::FixedPoint { generation: start },
+                        period => Outcome::Cycle { start, period },
+                    };
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(self.generation);
+                }
+            }
+        }
+        Outcome::Running
+    }
+
+    fn snapshot(&self) -> Vec<Point<D>> {
+        let mut cells: Vec<Point<D>> = self.cells.iter().copied().collect();
+        cells.sort_unstable();
+        cells
+    }
+}
+
+#[inline]
+fn add<const D: usize>(point: &Point<D>, delta: &Point<D>) -> Point<D> {
+    let mut sum = *point;
+    for (axis, offset) in sum.iter_mut().zip(delta.iter()) {
+        *axis += offset;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plane(input: &str) -> Grid<bool> {
+        Grid::parse(input, |c| Some(c == '#'), "a cell").unwrap()
+    }
+
+    #[test]
+    fn test_neighbour_deltas() {
+        assert_eq!(vec![[-1], [1]], neighbour_deltas::<1>());
+        assert_eq!(8, neighbour_deltas::<2>().len());
+        assert_eq!(26, neighbour_deltas::<3>().len());
+        assert_eq!(80, neighbour_deltas::<4>().len());
+    }
+
+    #[test]
+    fn test_rule_parse() {
+        assert_eq!(Ok(Rule::conway()), Rule::parse("B3/S23"));
+        assert_eq!(Ok(Rule::conway()), Rule::parse("S23/B3"));
+        assert_eq!("B36/S23", Rule::parse("B36/S23").unwrap().to_string());
+        assert_eq!("B3,10/S", Rule::parse("B10,3/S").unwrap().to_string());
+    }
+
+    #[test]
+    fn test_rule_parse_errors() {
+        assert_eq!(
+            Err(Error::parse(
+                1,
+                4,
+                "expected 'B<counts>' or 'S<counts>', found 'X23'"
+            )),
+            Rule::parse("B3/X23")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 7, "expected a neighbour count, found 'x'")),
+            Rule::parse("B3/S23x")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 4, "'B' is given twice")),
+            Rule::parse("B3/B4")
+        );
+        assert_eq!(
+            Err(Error::input(
+                "expected a rule of the form 'B<counts>/S<counts>'"
+            )),
+            Rule::parse("B3")
+        );
+        assert_eq!(
+            Err(Error::input("B0 rules would fill all of space")),
+            Rule::parse("B03/S23")
+        );
+    }
+
+    #[test]
+    fn test_step() {
+        let mut automaton =
+            Automaton::<2>::from_plane(&plane(".#.\n.#.\n.#."), |c| *c, Rule::conway());
+        assert_eq!(3, automaton.live_neighbours(&[1, 0]));
+        assert!(automaton.next_state(&[1, 0]));
+        assert!(!automaton.next_state(&[0, 1]));
+
+        automaton.step();
+        assert_eq!(1, automaton.generation());
+        assert_eq!(3, automaton.population());
+        assert!(automaton.is_alive(&[1, 0]) && automaton.is_alive(&[1, 2]));
+        assert_eq!(Some(([1, 0], [1, 2])), automaton.bounds());
+    }
+
+    #[test]
+    fn test_render() {
+        let automaton = Automaton::new(vec![[0, 0, 0], [1, 1, 0], [0, 1, 1]], Rule::conway());
+        assert_eq!("#. .#\n.# ..", automaton.render('#', '.').to_string());
+
+        let automaton = Automaton::new(vec![[0, 0, 0, 0], [0, 0, 0, 1]], Rule::conway());
+        assert_eq!("#\n \n#", automaton.render('#', '.').to_string());
+
+        let automaton = Automaton::<2>::new(vec![], Rule::conway());
+        assert_eq!(".", automaton.render('#', '.').to_string());
+    }
+
+    #[test]
+    fn test_lonely_cells() {
+        let mut automaton = Automaton::new(vec![[0, 0]], Rule::parse("B3/S0").unwrap());
+        automaton.step();
+        assert_eq!(vec![&[0, 0]], automaton.cells().collect::<Vec<_>>());
+
+        let mut automaton = Automaton::new(vec![[0, 0]], Rule::conway());
+        automaton.step();
+        assert_eq!(None, automaton.bounds());
+    }
+
+    #[test]
+    fn test_run_until_repeat() {
+        let blinker = plane(".#.\n.#.\n.#.");
+        let mut automaton = Automaton::<2>::from_plane(&blinker, |c| *c, Rule::conway());
+        assert_eq!(
+            Outcome::Cycle {
+                start: 0,
+                period: 2
+            },
+            automaton.run_until_repeat(10)
+        );
+
+        let block = plane("##\n##");
+        let mut automaton =
+            Automaton::<3>::from_plane(&block, |c| *c, Rule::parse("B3/S23").unwrap());
+        assert_eq!(Outcome::Running, automaton.run_until_repeat(0));
+        assert_eq!(
+            Outcome::FixedPoint { generation: 0 },
+            automaton.run_until_repeat(10)
+        );
+
+        let glider = plane(".#.\n..#\n###");
+        let mut automaton = Automaton::<2>::from_plane(&glider, |c| *c, Rule::conway());
+        assert_eq!(Outcome::Running, automaton.run_until_repeat(20));
+    }
+
+    #[test]
+    fn test_run_in_3_dimensions() {
+        let mut automaton =
+            Automaton::<3>::from_plane(&plane(".#.\n..#\n###"), |c| *c, Rule::conway());
+        automaton.run(6);
+        assert_eq!(112, automaton.population());
+    }
+}
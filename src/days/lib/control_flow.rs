@@ -0,0 +1,253 @@
+extern crate petgraph;
+use self::petgraph::algo::{has_path_connecting, tarjan_scc};
+use self::petgraph::dot::{Config, Dot};
+use self::petgraph::graph::DiGraph;
+use self::petgraph::graphmap::DiGraphMap;
+use self::petgraph::visit::{Dfs, Reversed};
+
+use super::handheld::Instruction;
+
+/// A run of instructions that always execute one after the other, from `start` up to but not
+/// including `end`. `successor` is where the last one leads: the start of another block, the
+/// end of the program, or nowhere if it jumps outside of the program.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub end: usize,
+    pub successor: Option<usize>,
+}
+
+/// Where each instruction of a handheld program leads. There is a node for every instruction
+/// and one more, numbered after the last instruction, for the end of the program.
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph {
+    graph: DiGraphMap<usize, ()>,
+    len: usize,
+}
+
+impl ControlFlowGraph {
+    pub fn new(program: &[Instruction]) -> Self {
+        let len = program.len();
+        let mut graph = DiGraphMap::new();
+        for program_counter in 0..=len {
+            graph.add_node(program_counter);
+        }
+        for (program_counter, instruction) in program.iter().enumerate() {
+            if let Some(successor) = instruction.successor(program_counter, len) {
+                graph.add_edge(program_counter, successor, ());
+            }
+        }
+
+        ControlFlowGraph { graph, len }
+    }
+
+    /// The node for the end of the program.
+    pub fn end(&self) -> usize {
+        self.len
+    }
+
+    pub fn successor(&self, program_counter: usize) -> Option<usize> {
+        self.graph.neighbors(program_counter).next()
+    }
+
+    /// Whether running the program from its first instruction gets to the end.
+    pub fn terminates(&self) -> bool {
+        has_path_connecting(&self.graph, 0, self.len, None)
+    }
+
+    /// The instructions that can't be reached from the first one.
+    pub fn unreachable(&self) -> Vec<usize> {
+        let mut reachable = vec![false; self.len + 1];
+        let mut dfs = Dfs::new(&self.graph, 0);
+        while let Some(program_counter) = dfs.next(&self.graph) {
+            reachable[program_counter] = true;
+        }
+
+        (0..self.len)
+            .filter(|program_counter| !reachable[*program_counter])
+            .collect()
+    }
+
+    /// Which instructions lead to the end of the program, with one more entry for the end
+    /// itself. Found by walking the graph backwards from the end.
+    pub fn reaches_end(&self) -> Vec<bool> {
+        let mut reaches = vec![false; self.len + 1];
+        let reversed = Reversed(&self.graph);
+        let mut dfs = Dfs::new(reversed, self.len);
+        while let Some(program_counter) = dfs.next(reversed) {
+            reaches[program_counter] = true;
+        }
+        reaches
+    }
+
+    /// Every cycle of instructions, each in order of position and ordered by their first
+    /// instruction. As every instruction has a single successor, a program that reaches one of
+    /// these never gets out of it again.
+    pub fn loops(&self) -> Vec<Vec<usize>> {
+        let mut loops: Vec<Vec<usize>> = tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.graph.contains_edge(component[0], component[0])
+            })
+            .map(|mut component| {
+                component.sort_unstable();
+                component
+            })
+            .collect();
+        loops.sort();
+        loops
+    }
+
+    /// The program split at every jump and every jump target.
+    pub fn basic_blocks(&self, program: &[Instruction]) -> Vec<BasicBlock> {
+        let mut leaders = vec![false; self.len + 1];
+        leaders[0] = true;
+        leaders[self.len] = true;
+        for (program_counter, instruction) in program.iter().enumerate() {
+            if let Instruction::Jmp(_) = instruction {
+                leaders[program_counter + 1] = true;
+                if let Some(successor) = self.successor(program_counter) {
+                    leaders[successor] = true;
+                }
+            }
+        }
+
+        let starts: Vec<usize> = (0..=self.len).filter(|idx| leaders[*idx]).collect();
+        starts
+            .windows(2)
+            .map(|bounds| BasicBlock {
+                start: bounds[0],
+                end: bounds[1],
+                successor: self.successor(bounds[1] - 1),
+            })
+            .collect()
+    }
+
+    /// The graph in Graphviz's DOT language, with each instruction labelled by its position.
+    pub fn to_dot(&self, program: &[Instruction]) -> String {
+        let mut graph: DiGraph<String, &str> = DiGraph::new();
+        let nodes: Vec<_> = (0..=self.len)
+            .map(|program_counter| {
+                graph.add_node(match program.get(program_counter) {
+                    Some(instruction) => format!("{}: {}", program_counter, instruction),
+                    None => "end".to_string(),
+                })
+            })
+            .collect();
+        for (from, to, _) in self.graph.all_edges() {
+            graph.add_edge(nodes[from], nodes[to], "");
+        }
+        format!("{}", Dot::with_config(&graph, &[Config::EdgeNoLabel]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use days::lib::handheld::{parse_program, TEST_CASE};
+
+    fn graph(program: &str) -> (Vec<Instruction>, ControlFlowGraph) {
+        let program = parse_program(program).unwrap();
+        let graph = ControlFlowGraph::new(&program);
+        (program, graph)
+    }
+
+    #[test]
+    fn test_successor() {
+        let (_, graph) = graph("nop +0\njmp -2\njmp +1");
+
+        assert_eq!(3, graph.end());
+        assert_eq!(Some(1), graph.successor(0));
+        assert_eq!(None, graph.successor(1));
+        assert_eq!(Some(3), graph.successor(2));
+        assert_eq!(None, graph.successor(3));
+    }
+
+    #[test]
+    fn test_terminates() {
+        assert!(!graph(TEST_CASE).1.terminates());
+        assert!(graph("nop +0\njmp +2\njmp +0\nacc +1").1.terminates());
+        assert!(!graph("jmp -1").1.terminates());
+    }
+
+    #[test]
+    fn test_unreachable() {
+        assert_eq!(vec![5, 8], graph(TEST_CASE).1.unreachable());
+        assert_eq!(vec![1], graph("jmp +2\njmp +0\nacc +1").1.unreachable());
+    }
+
+    #[test]
+    fn test_reaches_end() {
+        assert_eq!(
+            vec![false, false, false, false, false, false, false, false, true, true],
+            graph(TEST_CASE).1.reaches_end()
+        );
+        assert_eq!(
+            vec![true, false, true, true],
+            graph("jmp +2\njmp +0\nacc +1").1.reaches_end()
+        );
+    }
+
+    #[test]
+    fn test_loops() {
+        assert_eq!(vec![vec![1, 2, 3, 4, 6, 7]], graph(TEST_CASE).1.loops());
+        assert_eq!(
+            vec![vec![0, 1], vec![2]],
+            graph("nop +0\njmp -1\njmp +0").1.loops()
+        );
+        assert!(graph("acc +1\njmp +1").1.loops().is_empty());
+    }
+
+    #[test]
+    fn test_basic_blocks() {
+        let (program, graph) = graph(TEST_CASE);
+
+        assert_eq!(
+            vec![
+                BasicBlock {
+                    start: 0,
+                    end: 1,
+                    successor: Some(1)
+                },
+                BasicBlock {
+                    start: 1,
+                    end: 3,
+                    successor: Some(6)
+                },
+                BasicBlock {
+                    start: 3,
+                    end: 5,
+                    successor: Some(1)
+                },
+                BasicBlock {
+                    start: 5,
+                    end: 6,
+                    successor: Some(6)
+                },
+                BasicBlock {
+                    start: 6,
+                    end: 8,
+                    successor: Some(3)
+                },
+                BasicBlock {
+                    start: 8,
+                    end: 9,
+                    successor: Some(9)
+                },
+            ],
+            graph.basic_blocks(&program)
+        );
+        assert!(ControlFlowGraph::new(&[]).basic_blocks(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let (program, graph) = graph("acc +1\njmp -1");
+
+        assert_eq!(
+            "digraph {\n    0 [ label = \"0: acc +1\" ]\n    1 [ label = \"1: jmp -1\" ]\n    \
+             2 [ label = \"end\" ]\n    0 -> 1 [ ]\n    1 -> 0 [ ]\n}\n",
+            graph.to_dot(&program)
+        );
+    }
+}
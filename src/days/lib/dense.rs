@@ -0,0 +1,231 @@
+extern crate rayon;
+use self::rayon::prelude::*;
+
+use super::grid::Grid;
+
+/// Which cells count as the neighbours of a cell.
+pub enum Neighbourhood<T> {
+    /// The 8 cells around it.
+    Adjacent,
+    /// The first cell in each of the 8 directions for which the function doesn't hold, looking
+    /// through the cells for which it does. Whether a cell can be seen through is decided on
+    /// the initial cells, so it shouldn't change.
+    LineOfSight(fn(&T) -> bool),
+    /// Every other cell at most `radius` rows and columns away.
+    Radius(usize),
+}
+
+impl<T> Neighbourhood<T> {
+    fn positions(&self, grid: &Grid<T>, row: usize, col: usize) -> Vec<(usize, usize)> {
+        match self {
+            Neighbourhood::Adjacent => grid
+                .neighbours_8(row, col)
+                .map(|(position, _)| position)
+                .collect(),
+            Neighbourhood::LineOfSight(is_transparent) => grid
+                .line_of_sight(row, col, is_transparent)
+                .map(|(position, _)| position)
+                .collect(),
+            Neighbourhood::Radius(radius) => {
+                let radius = *radius as isize;
+                let offsets: Vec<(isize, isize)> = (-radius..=radius)
+                    .flat_map(|d_row| (-radius..=radius).map(move |d_col| (d_row, d_col)))
+                    .filter(|offset| *offset != (0, 0))
+                    .collect();
+                grid.neighbours(row, col, &offsets)
+                    .map(|(position, _)| position)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A cellular automaton over a fixed rectangle of cells. The neighbours of every cell are
+/// worked out once up front, and each generation is written into a second buffer, row by row
+/// in parallel, before the two are swapped.
+#[derive(Debug, Clone)]
+pub struct DenseAutomaton<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    next: Vec<T>,
+    neighbours: Vec<Vec<usize>>,
+    generation: usize,
+}
+
+impl<T: Clone + PartialEq + Send + Sync> DenseAutomaton<T> {
+    pub fn new(grid: &Grid<T>, neighbourhood: &Neighbourhood<T>) -> Self {
+        let width = grid.width();
+        let neighbours = grid
+            .iter()
+            .map(|((row, col), _)| {
+                neighbourhood
+                    .positions(grid, row, col)
+                    .into_iter()
+                    .map(|(n_row, n_col)| n_row * width + n_col)
+                    .collect()
+            })
+            .collect();
+        let cells: Vec<T> = grid.cells().cloned().collect();
+
+        DenseAutomaton {
+            width,
+            height: grid.height(),
+            next: cells.clone(),
+            cells,
+            neighbours,
+            generation: 0,
+        }
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn to_grid(&self) -> Grid<T> {
+        Grid::new(self.width, self.height, self.cells.clone())
+    }
+
+    pub fn neighbours(&self, row: usize, col: usize) -> Neighbours<'_, T> {
+        Neighbours {
+            cells: &self.cells,
+            indices: self.neighbours[row * self.width + col].iter(),
+        }
+    }
+
+    /// Moves to the next generation, where each cell is what `rule` makes of it and its
+    /// neighbours. Returns whether any cell changed.
+    pub fn step<F>(&mut self, rule: &F) -> bool
+    where
+        F: Fn(&T, Neighbours<'_, T>) -> T + Sync,
+    {
+        let (cells, neighbours, width) = (&self.cells, &self.neighbours, self.width);
+        let changed = self
+            .next
+            .par_chunks_mut(width.max(1))
+            .enumerate()
+            .map(|(row, next_row)| {
+                let mut changed = false;
+                for (col, next_cell) in next_row.iter_mut().enumerate() {
+                    let idx = row * width + col;
+                    let neighbours = Neighbours {
+                        cells,
+                        indices: neighbours[idx].iter(),
+                    };
+                    *next_cell = rule(&cells[idx], neighbours);
+                    changed |= *next_cell != cells[idx];
+                }
+                changed
+            })
+            .reduce(|| false, |left, right| left || right);
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.generation += 1;
+        changed
+    }
+
+    /// Steps until nothing changes any more, returning how many generations changed something.
+    pub fn run_until_stable<F>(&mut self, rule: &F) -> usize
+    where
+        F: Fn(&T, Neighbours<'_, T>) -> T + Sync,
+    {
+        let mut iterations = 0;
+        while self.step(rule) {
+            iterations += 1;
+        }
+        iterations
+    }
+}
+
+/// The current neighbours of a cell.
+pub struct Neighbours<'a, T> {
+    cells: &'a [T],
+    indices: std::slice::Iter<'a, usize>,
+}
+
+impl<'a, T> Iterator for Neighbours<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.indices.next().map(|idx| &self.cells[*idx])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(input: &str) -> Grid<char> {
+        Grid::parse(input, Some, "a cell").unwrap()
+    }
+
+    fn life(cell: &char, neighbours: Neighbours<char>) -> char {
+        match (cell, neighbours.filter(|c| **c == '#').count()) {
+            ('#', 2) | (_, 3) => '#',
+            _ => '.',
+        }
+    }
+
+    #[test]
+    fn test_neighbourhoods() {
+        let grid = grid("#...\n.#..\n..|.\n...#");
+        let neighbours = |automaton: &DenseAutomaton<char>, row, col| {
+            automaton.neighbours(row, col).collect::<String>()
+        };
+
+        let adjacent = DenseAutomaton::new(&grid, &Neighbourhood::Adjacent);
+        assert_eq!("#......|", neighbours(&adjacent, 1, 1));
+        assert_eq!(3, adjacent.neighbours(0, 0).count());
+
+        let radius = DenseAutomaton::new(&grid, &Neighbourhood::Radius(2));
+        assert_eq!(8, radius.neighbours(0, 0).count());
+        assert_eq!(15, radius.neighbours(2, 2).count());
+
+        let line_of_sight = DenseAutomaton::new(&grid, &Neighbourhood::LineOfSight(|c| *c == '.'));
+        assert_eq!("#|", neighbours(&line_of_sight, 1, 1));
+        assert_eq!("|", neighbours(&line_of_sight, 3, 3));
+    }
+
+    #[test]
+    fn test_step() {
+        let mut automaton = DenseAutomaton::new(
+            &grid(".....\n..#..\n..#..\n..#..\n....."),
+            &Neighbourhood::Adjacent,
+        );
+
+        assert!(automaton.step(&life));
+        assert_eq!(1, automaton.generation());
+        assert_eq!(
+            ".....\n.....\n.###.\n.....\n.....",
+            automaton.to_grid().to_string()
+        );
+        assert!(automaton.step(&life));
+        assert_eq!(
+            ".....\n..#..\n..#..\n..#..\n.....",
+            automaton.to_grid().to_string()
+        );
+    }
+
+    #[test]
+    fn test_run_until_stable() {
+        let mut automaton =
+            DenseAutomaton::new(&grid("....\n.##.\n.#..\n...."), &Neighbourhood::Adjacent);
+
+        assert_eq!(1, automaton.run_until_stable(&life));
+        assert_eq!(2, automaton.generation());
+        assert_eq!(4, automaton.cells().filter(|c| **c == '#').count());
+    }
+
+    #[test]
+    fn test_empty() {
+        let grid: Grid<char> = Grid::new(0, 2, Vec::new());
+        let mut automaton = DenseAutomaton::new(&grid, &Neighbourhood::Adjacent);
+
+        assert!(!automaton.step(&life));
+        assert_eq!(grid, automaton.to_grid());
+    }
+}
@@ -0,0 +1,712 @@
+use super::parse_token;
+use error::{Error, Result};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    /// Division rounding towards zero.
+    Divide,
+    /// The remainder of `Divide`, with the sign of the dividend.
+    Remainder,
+    Power,
+}
+
+impl Operator {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Operator::Add),
+            '-' => Some(Operator::Subtract),
+            '*' => Some(Operator::Multiply),
+            '/' => Some(Operator::Divide),
+            '%' => Some(Operator::Remainder),
+            '^' => Some(Operator::Power),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+            Operator::Remainder => '%',
+            Operator::Power => '^',
+        }
+    }
+
+    /// Whether `a op b op c` is `a op (b op c)` rather than `(a op b) op c`.
+    pub fn is_right_associative(&self) -> bool {
+        *self == Operator::Power
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Result<i64> {
+        let result = match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide | Operator::Remainder if right == 0 => {
+                return Err(Error::input(format!(
+                    "division by zero in '{} {} {}'",
+                    left,
+                    self.symbol(),
+                    right
+                )))
+            }
+            Operator::Divide => left.checked_div(right),
+            Operator::Remainder => left.checked_rem(right),
+            Operator::Power => return power(left, right),
+        };
+        result.ok_or_else(|| overflow(format!("{} {} {}", left, self.symbol(), right)))
+    }
+}
+
+fn power(base: i64, exponent: i64) -> Result<i64> {
+    if exponent < 0 {
+        return Err(Error::input(format!(
+            "negative exponent in '{} ^ {}'",
+            base, exponent
+        )));
+    }
+    match (base, u32::try_from(exponent)) {
+        (0, _) | (1, _) => Some(base),
+        (-1, _) => Some(if exponent % 2 == 0 { 1 } else { -1 }),
+        (_, Ok(exponent)) => base.checked_pow(exponent),
+        (_, Err(_)) => None,
+    }
+    .ok_or_else(|| overflow(format!("{} ^ {}", base, exponent)))
+}
+
+fn overflow(operation: String) -> Error {
+    Error::input(format!("'{}' overflows", operation))
+}
+
+/// How tightly each operator binds, where higher binds tighter and 0 is the loosest. Operators
+/// of the same precedence are evaluated from left to right, unless they're right associative,
+/// and operators missing from the table aren't allowed at all. A '-' in front of an operand
+/// negates it if subtraction is allowed, and binds tighter than any operator other than '^'.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Precedence {
+    levels: Vec<(Operator, u8)>,
+}
+
+impl Precedence {
+    pub fn new(levels: &[(Operator, u8)]) -> Self {
+        Precedence {
+            levels: levels.to_vec(),
+        }
+    }
+
+    /// Addition and multiplication from left to right, as in the first part of day 18.
+    pub fn left_to_right() -> Self {
+        Precedence::new(&[(Operator::Add, 1), (Operator::Multiply, 1)])
+    }
+
+    /// Addition before multiplication, as in the second part of day 18.
+    pub fn addition_first() -> Self {
+        Precedence::new(&[(Operator::Add, 2), (Operator::Multiply, 1)])
+    }
+
+    /// Every operator, with the usual precedence.
+    pub fn standard() -> Self {
+        Precedence::new(&[
+            (Operator::Add, 1),
+            (Operator::Subtract, 1),
+            (Operator::Multiply, 2),
+            (Operator::Divide, 2),
+            (Operator::Remainder, 2),
+            (Operator::Power, 3),
+        ])
+    }
+
+    /// One more than the operator's precedence, so that even the loosest operators bind
+    /// tighter than the start of an expression.
+    fn binding_power(&self, operator: Operator) -> Option<u16> {
+        self.levels
+            .iter()
+            .find(|(level_operator, _)| *level_operator == operator)
+            .map(|(_, precedence)| u16::from(*precedence) + 1)
+    }
+
+    /// How tightly a '-' in front of an operand binds, if it's allowed.
+    fn negation_power(&self) -> Option<u16> {
+        self.binding_power(Operator::Subtract)?;
+        self.levels
+            .iter()
+            .filter(|(operator, _)| *operator != Operator::Power)
+            .map(|(operator, _)| self.binding_power(*operator))
+            .max()?
+    }
+
+    /// "'+', '*' or ')'", for errors about what could have come after an operand.
+    fn expected_after_operand(&self) -> String {
+        let operators: Vec<String> = self
+            .levels
+            .iter()
+            .map(|(operator, _)| format!("'{}'", operator.symbol()))
+            .collect();
+        format!("{} or ')'", operators.join(", "))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TokenKind {
+    Number(i64),
+    /// A letter or '_', followed by any letters, digits and '_'.
+    Name,
+    OpenBracket,
+    CloseBracket,
+    /// Any other character, which may or may not be an operator.
+    Symbol(char),
+}
+
+/// A token, and the text it was read from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+}
+
+/// Splits `line`, a slice of `input`, into numbers, names, brackets and single character
+/// symbols, skipping whitespace.
+pub fn tokenize<'a>(input: &str, line: &'a str) -> Result<Vec<Token<'a>>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' => {
+                while let Some((idx, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = idx + 1;
+                }
+                TokenKind::Number(parse_token(input, &line[start..end], "a number")?)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                while let Some((idx, _)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    end = idx + 1;
+                }
+                TokenKind::Name
+            }
+            '(' => TokenKind::OpenBracket,
+            ')' => TokenKind::CloseBracket,
+            c => TokenKind::Symbol(c),
+        };
+        tokens.push(Token {
+            kind,
+            text: &line[start..end],
+        });
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expression {
+    Number(i64),
+    Variable(String),
+    Negate(Box<Expression>),
+    Binary {
+        operator: Operator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+}
+
+impl Expression {
+    /// Parses `line`, a slice of `input`, with operators binding as `precedence` says.
+    pub fn parse(input: &str, line: &str, precedence: &Precedence) -> Result<Self> {
+        let mut parser = Parser {
+            input,
+            end: &line[line.len()..],
+            tokens: tokenize(input, line)?,
+            position: 0,
+            depth: 0,
+            precedence,
+        };
+        parser.expression(0)
+    }
+
+    /// The value of the expression, failing on undefined variables, division by zero, negative
+    /// exponents and anything that doesn't fit in an `i64`.
+    pub fn eval(&self, variables: &HashMap<String, i64>) -> Result<i64> {
+        self.evaluate(variables, None)
+    }
+
+    /// Like `eval`, along with every operation in the order they're worked out, such as
+    /// "1 + 2 = 3".
+    pub fn eval_steps(&self, variables: &HashMap<String, i64>) -> Result<(i64, Vec<String>)> {
+        let mut steps = Vec::new();
+        let value = self.evaluate(variables, Some(&mut steps))?;
+        Ok((value, steps))
+    }
+
+    fn evaluate(
+        &self,
+        variables: &HashMap<String, i64>,
+        mut steps: Option<&mut Vec<String>>,
+    ) -> Result<i64> {
+        let (operation, value) = match self {
+            Expression::Number(value) => return Ok(*value),
+            Expression::Variable(name) => {
+                return variables
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| Error::input(format!("'{}' isn't defined", name)))
+            }
+            Expression::Negate(operand) => {
+                let value = operand.evaluate(variables, steps.as_deref_mut())?;
+                let operation = format!("-({})", value);
+                match value.checked_neg() {
+                    Some(negated) => (operation, negated),
+                    None => return Err(overflow(operation)),
+                }
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let left = left.evaluate(variables, steps.as_deref_mut())?;
+                let right = right.evaluate(variables, steps.as_deref_mut())?;
+                let operation = format!("{} {} {}", left, operator.symbol(), right);
+                (operation, operator.apply(left, right)?)
+            }
+        };
+
+        if let Some(steps) = steps {
+            steps.push(format!("{} = {}", operation, value));
+        }
+        Ok(value)
+    }
+
+    /// The expression as a tree, one node per line, with operands indented under their
+    /// operator.
+    pub fn tree(&self) -> String {
+        let mut lines = Vec::new();
+        self.add_tree_lines(0, &mut lines);
+        lines.join("\n")
+    }
+
+    fn add_tree_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        match self {
+            Expression::Number(value) => lines.push(format!("{}{}", indent, value)),
+            Expression::Variable(name) => lines.push(format!("{}{}", indent, name)),
+            Expression::Negate(operand) => {
+                lines.push(format!("{}-", indent));
+                operand.add_tree_lines(depth + 1, lines);
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                lines.push(format!("{}{}", indent, operator.symbol()));
+                left.add_tree_lines(depth + 1, lines);
+                right.add_tree_lines(depth + 1, lines);
+            }
+        }
+    }
+}
+
+/// Every operation in brackets, so that the order of evaluation shows.
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{}", value),
+            Expression::Variable(name) => write!(f, "{}", name),
+            Expression::Negate(operand) => write!(f, "(-{})", operand),
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => write!(f, "({} {} {})", left, operator.symbol(), right),
+        }
+    }
+}
+
+/// A Pratt parser over the tokens of one expression.
+struct Parser<'a, 'b> {
+    input: &'a str,
+    /// The empty slice at the end of the expression, for errors about it ending too soon.
+    end: &'a str,
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    /// How many brackets are open.
+    depth: usize,
+    precedence: &'b Precedence,
+}
+
+impl<'a, 'b> Parser<'a, 'b> {
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    /// An expression made of operators that bind tighter than `min_power`.
+    fn expression(&mut self, min_power: u16) -> Result<Expression> {
+        let mut left = self.operand()?;
+
+        while let Some(token) = self.peek() {
+            let operator = match token.kind {
+                TokenKind::CloseBracket if self.depth > 0 => break,
+                TokenKind::CloseBracket => {
+                    return Err(Error::at(self.input, token.text, "unexpected ')'"))
+                }
+                TokenKind::Symbol(c) => Operator::from_char(c).and_then(|operator| {
+                    Some((operator, self.precedence.binding_power(operator)?))
+                }),
+                _ => None,
+            };
+            let (operator, power) = operator.ok_or_else(|| {
+                let message = format!(
+                    "expected {}, found '{}'",
+                    self.precedence.expected_after_operand(),
+                    token.text
+                );
+                Error::at(self.input, token.text, message)
+            })?;
+            if power <= min_power {
+                break;
+            }
+
+            self.next();
+            let right = if operator.is_right_associative() {
+                self.expression(power - 1)?
+            } else {
+                self.expression(power)?
+            };
+            left = Expression::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    /// A number, a variable, a negated operand or an expression in brackets.
+    fn operand(&mut self) -> Result<Expression> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => return Err(Error::at(self.input, self.end, "expected a number")),
+        };
+
+        match token.kind {
+            TokenKind::Number(value) => Ok(Expression::Number(value)),
+            TokenKind::Name => Ok(Expression::Variable(token.text.to_string())),
+            TokenKind::OpenBracket => {
+                self.depth += 1;
+                let expression = self.expression(0)?;
+                if self.next().is_none() {
+                    return Err(Error::at(self.input, token.text, "unclosed '('"));
+                }
+                self.depth -= 1;
+                Ok(expression)
+            }
+            TokenKind::Symbol('-') => match self.precedence.negation_power() {
+                Some(power) => Ok(Expression::Negate(Box::new(self.expression(power)?))),
+                None => Err(self.expected_operand(token)),
+            },
+            _ => Err(self.expected_operand(token)),
+        }
+    }
+
+    fn expected_operand(&self, token: Token) -> Error {
+        Error::at(
+            self.input,
+            token.text,
+            format!("expected a number or '(', found '{}'", token.text),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(expression: &str, precedence: &Precedence) -> Result<Expression> {
+        Expression::parse(expression, expression, precedence)
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let line = "(12 + 3)*!";
+        assert_eq!(
+            Ok(vec![
+                Token {
+                    kind: TokenKind::OpenBracket,
+                    text: "("
+                },
+                Token {
+                    kind: TokenKind::Number(12),
+                    text: "12"
+                },
+                Token {
+                    kind: TokenKind::Symbol('+'),
+                    text: "+"
+                },
+                Token {
+                    kind: TokenKind::Number(3),
+                    text: "3"
+                },
+                Token {
+                    kind: TokenKind::CloseBracket,
+                    text: ")"
+                },
+                Token {
+                    kind: TokenKind::Symbol('*'),
+                    text: "*"
+                },
+                Token {
+                    kind: TokenKind::Symbol('!'),
+                    text: "!"
+                },
+            ]),
+            tokenize(line, line)
+        );
+        assert_eq!(
+            Err(Error::parse(
+                1,
+                3,
+                "expected a number, found '99999999999999999999'"
+            )),
+            tokenize("1 99999999999999999999", "1 99999999999999999999")
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let expression = "1 + 2 * 3 + 4";
+        assert_eq!(
+            "(((1 + 2) * 3) + 4)",
+            parse(expression, &Precedence::left_to_right())
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "((1 + 2) * (3 + 4))",
+            parse(expression, &Precedence::addition_first())
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "((1 + (2 * 3)) + 4)",
+            parse(expression, &Precedence::standard())
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            Ok(Expression::Number(100)),
+            parse("((100))", &Precedence::standard())
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let precedence = Precedence::left_to_right();
+        assert_eq!(
+            Err(Error::parse(1, 5, "expected a number or '(', found '*'")),
+            parse("3 + * 4", &precedence)
+        );
+        assert_eq!(
+            Err(Error::parse(1, 8, "expected '+', '*' or ')', found '2'")),
+            parse("(1 + 2 2)", &precedence)
+        );
+        assert_eq!(
+            Err(Error::parse(1, 3, "expected '+' or ')', found '*'")),
+            parse("1 * 2", &Precedence::new(&[(Operator::Add, 1)]))
+        );
+        assert_eq!(
+            Err(Error::parse(1, 2, "unclosed '('")),
+            parse(" ((1 + 2)", &precedence)
+        );
+        assert_eq!(
+            Err(Error::parse(1, 6, "unexpected ')'")),
+            parse("1 + 2)", &precedence)
+        );
+        assert_eq!(
+            Err(Error::parse(1, 4, "expected a number")),
+            parse("1 +", &precedence)
+        );
+        assert_eq!(
+            Err(Error::parse(1, 1, "expected a number")),
+            parse("", &precedence)
+        );
+    }
+
+    #[test]
+    fn test_eval() {
+        let expression = "2 * 3 + (4 * 5)";
+        assert_eq!(
+            Ok(26),
+            parse(expression, &Precedence::left_to_right())
+                .unwrap()
+                .eval(&HashMap::new())
+        );
+        assert_eq!(
+            Ok(46),
+            parse(expression, &Precedence::addition_first())
+                .unwrap()
+                .eval(&HashMap::new())
+        );
+        assert_eq!(
+            Ok(26),
+            parse(expression, &Precedence::standard())
+                .unwrap()
+                .eval(&HashMap::new())
+        );
+        assert_eq!(
+            Ok(150),
+            parse("(12 + 3) * 10", &Precedence::standard())
+                .unwrap()
+                .eval(&HashMap::new())
+        );
+    }
+
+    #[test]
+    fn test_tokenize_names() {
+        let line = "x_1 *y";
+        assert_eq!(
+            Ok(vec![
+                Token {
+                    kind: TokenKind::Name,
+                    text: "x_1"
+                },
+                Token {
+                    kind: TokenKind::Symbol('*'),
+                    text: "*"
+                },
+                Token {
+                    kind: TokenKind::Name,
+                    text: "y"
+                },
+            ]),
+            tokenize(line, line)
+        );
+    }
+
+    #[test]
+    fn test_parse_extended_operators() {
+        let precedence = Precedence::standard();
+        let parsed = |expression| parse(expression, &precedence).unwrap().to_string();
+
+        assert_eq!("((10 - 4) - 3)", parsed("10 - 4 - 3"));
+        assert_eq!("(1 - ((6 / 2) % 2))", parsed("1 - 6 / 2 % 2"));
+        assert_eq!("(2 ^ (3 ^ 2))", parsed("2 ^ 3 ^ 2"));
+        assert_eq!("((-1) - (-(-2)))", parsed("-1 - --2"));
+        assert_eq!("(-(2 ^ 2))", parsed("-2 ^ 2"));
+        assert_eq!("(3 * (-x))", parsed("3 * -x"));
+        assert_eq!(
+            Err(Error::parse(1, 1, "expected a number or '(', found '-'")),
+            parse("-1", &Precedence::left_to_right())
+        );
+        assert_eq!(
+            Err(Error::parse(
+                1,
+                3,
+                "expected '+', '-', '*', '/', '%', '^' or ')', found '!'"
+            )),
+            parse("1 ! 2", &precedence)
+        );
+    }
+
+    #[test]
+    fn test_eval_extended_operators() {
+        let variables: HashMap<String, i64> = vec![("x".to_string(), 7), ("y".to_string(), -2)]
+            .into_iter()
+            .collect();
+        let eval = |expression| {
+            parse(expression, &Precedence::standard())
+                .unwrap()
+                .eval(&variables)
+        };
+
+        assert_eq!(Ok(3), eval("10 - 4 - 3"));
+        assert_eq!(Ok(-3), eval("-7 / 2"));
+        assert_eq!(Ok(-1), eval("-7 % 2"));
+        assert_eq!(Ok(512), eval("2 ^ 3 ^ 2"));
+        assert_eq!(Ok(-4), eval("-2 ^ 2"));
+        assert_eq!(Ok(1), eval("(-1) ^ 9223372036854775806"));
+        assert_eq!(Ok(-11), eval("x * y + 3"));
+        assert_eq!(Ok(i64::MIN), eval("-9223372036854775807 - 1"));
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        let eval = |expression| {
+            parse(expression, &Precedence::standard())
+                .unwrap()
+                .eval(&HashMap::new())
+        };
+
+        assert_eq!(Err(Error::input("'z' isn't defined")), eval("1 + z"));
+        assert_eq!(
+            Err(Error::input("division by zero in '1 / 0'")),
+            eval("1 / (2 - 2)")
+        );
+        assert_eq!(
+            Err(Error::input("division by zero in '1 % 0'")),
+            eval("1 % 0")
+        );
+        assert_eq!(
+            Err(Error::input("negative exponent in '2 ^ -1'")),
+            eval("2 ^ -1")
+        );
+        assert_eq!(Err(Error::input("'2 ^ 63' overflows")), eval("2 ^ 63"));
+        assert_eq!(
+            Err(Error::input("'2 ^ 9999999999' overflows")),
+            eval("2 ^ 9999999999")
+        );
+        assert_eq!(
+            Err(Error::input("'-(-9223372036854775808)' overflows")),
+            eval("-(-9223372036854775807 - 1)")
+        );
+        assert_eq!(
+            Err(Error::input("'-9223372036854775808 / -1' overflows")),
+            eval("(-9223372036854775807 - 1) / -1")
+        );
+    }
+
+    #[test]
+    fn test_eval_steps() {
+        let expression = parse("2 * 3 + -(4 * 5)", &Precedence::standard()).unwrap();
+        assert_eq!(
+            Ok((
+                -14,
+                vec![
+                    "2 * 3 = 6".to_string(),
+                    "4 * 5 = 20".to_string(),
+                    "-(20) = -20".to_string(),
+                    "6 + -20 = -14".to_string(),
+                ]
+            )),
+            expression.eval_steps(&HashMap::new())
+        );
+        assert_eq!(
+            Err(Error::input("'-(-9223372036854775808)' overflows")),
+            parse("-(-9223372036854775807 - 1)", &Precedence::standard())
+                .unwrap()
+                .eval_steps(&HashMap::new())
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        let expression = parse("1 + 2 * -x", &Precedence::standard()).unwrap();
+        assert_eq!("+\n  1\n  *\n    2\n    -\n      x", expression.tree());
+    }
+}
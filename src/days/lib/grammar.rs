@@ -0,0 +1,263 @@
+use std::collections::{BTreeSet, HashMap};
+
+/// One part of a sequence: another rule, or text that has to appear as is.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Symbol {
+    Rule(String),
+    Literal(String),
+}
+
+/// Named rules, each a list of alternative sequences of symbols. Rules can refer to each other
+/// and to themselves in any way, including at the start of a sequence.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Grammar {
+    rules: HashMap<String, Vec<Vec<Symbol>>>,
+}
+
+impl Grammar {
+    pub fn new() -> Self {
+        Grammar::default()
+    }
+
+    /// Adds a rule, replacing any other rule with the same name.
+    pub fn insert(&mut self, name: &str, alternatives: Vec<Vec<Symbol>>) {
+        self.rules.insert(name.to_string(), alternatives);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.rules.contains_key(name)
+    }
+
+    /// Whether the whole of `message` matches `rule`.
+    pub fn matches(&self, rule: &str, message: &str) -> bool {
+        self.ends(rule, message, 0).contains(&message.len())
+    }
+
+    /// Every offset in `message` that a match of `rule` starting at `offset` can end at. A rule
+    /// that isn't defined matches nothing.
+    pub fn ends(&self, rule: &str, message: &str, offset: usize) -> BTreeSet<usize> {
+        let mut matcher = Matcher {
+            grammar: self,
+            message,
+            memo: HashMap::new(),
+            in_progress: HashMap::new(),
+        };
+        match self.rules.get_key_value(rule) {
+            Some((rule, _)) => matcher.ends(rule, offset).0,
+            None => BTreeSet::new(),
+        }
+    }
+}
+
+/// Matches rules by recursive descent, remembering the ends found for each rule and offset so
+/// that none is matched twice. A rule that comes back to itself at the same offset would recurse
+/// forever, so in that case the inner call gets the ends found so far instead, and the rule is
+/// matched again until that no longer changes them. Anything matched using ends that aren't
+/// final yet isn't remembered.
+struct Matcher<'a> {
+    grammar: &'a Grammar,
+    message: &'a str,
+    memo: HashMap<(&'a str, usize), BTreeSet<usize>>,
+    /// The ends found so far for each rule being matched, how deep it is in the recursion, and
+    /// whether the ends were used.
+    in_progress: HashMap<(&'a str, usize), (BTreeSet<usize>, usize, bool)>,
+}
+
+impl<'a> Matcher<'a> {
+    /// The ends of `rule` at `offset`, and the depth of the outermost rule still being matched
+    /// whose ends so far they were found with, if any.
+    fn ends(&mut self, rule: &'a str, offset: usize) -> (BTreeSet<usize>, Option<usize>) {
+        let key = (rule, offset);
+        if let Some(ends) = self.memo.get(&key) {
+            return (ends.clone(), None);
+        }
+        if let Some((ends, depth, used)) = self.in_progress.get_mut(&key) {
+            *used = true;
+            return (ends.clone(), Some(*depth));
+        }
+
+        let depth = self.in_progress.len();
+        self.in_progress
+            .insert(key, (BTreeSet::new(), depth, false));
+        loop {
+            let (ends, outermost) = self.alternative_ends(rule, offset);
+            let (previous, _, used) = self.in_progress.get_mut(&key).unwrap();
+            if !*used || ends == *previous {
+                self.in_progress.remove(&key);
+                return match outermost {
+                    Some(outermost) if outermost < depth => (ends, Some(outermost)),
+                    _ => {
+                        self.memo.insert(key, ends.clone());
+                        (ends, None)
+                    }
+                };
+            }
+            *previous = ends;
+            *used = false;
+        }
+    }
+
+    fn alternative_ends(
+        &mut self,
+        rule: &'a str,
+        offset: usize,
+    ) -> (BTreeSet<usize>, Option<usize>) {
+        let grammar = self.grammar;
+        let mut ends = BTreeSet::new();
+        let mut outermost = None;
+        for sequence in grammar.rules.get(rule).into_iter().flatten() {
+            let mut offsets: BTreeSet<usize> = Some(offset).into_iter().collect();
+            for symbol in sequence {
+                let mut next = BTreeSet::new();
+                for offset in offsets {
+                    let (symbol_ends, depth) = self.symbol_ends(symbol, offset);
+                    next.extend(symbol_ends);
+                    outermost = outermost.into_iter().chain(depth).min();
+                }
+                offsets = next;
+                if offsets.is_empty() {
+                    break;
+                }
+            }
+            ends.extend(offsets);
+        }
+        (ends, outermost)
+    }
+
+    fn symbol_ends(
+        &mut self,
+        symbol: &'a Symbol,
+        offset: usize,
+    ) -> (BTreeSet<usize>, Option<usize>) {
+        match symbol {
+            Symbol::Literal(text) => (
+                Some(offset + text.len())
+                    .filter(|_| self.message[offset..].starts_with(text.as_str()))
+                    .into_iter()
+                    .collect(),
+                None,
+            ),
+            Symbol::Rule(rule) => self.ends(rule, offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str) -> Symbol {
+        Symbol::Rule(name.to_string())
+    }
+
+    fn literal(text: &str) -> Symbol {
+        Symbol::Literal(text.to_string())
+    }
+
+    fn letters() -> Grammar {
+        let mut grammar = Grammar::new();
+        grammar.insert("a", vec![vec![literal("a")]]);
+        grammar.insert("b", vec![vec![literal("b")]]);
+        grammar
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut grammar = letters();
+        grammar.insert("0", vec![vec![rule("a"), rule("1")]]);
+        grammar.insert(
+            "1",
+            vec![vec![rule("a"), rule("b")], vec![rule("b"), rule("a")]],
+        );
+
+        assert!(grammar.matches("0", "aab"));
+        assert!(grammar.matches("0", "aba"));
+        assert!(!grammar.matches("0", "abb"));
+        assert!(!grammar.matches("0", "aabb"));
+        assert!(!grammar.matches("0", "aa"));
+        assert!(!grammar.matches("2", "a"));
+    }
+
+    #[test]
+    fn test_ends() {
+        let mut grammar = letters();
+        grammar.insert("0", vec![vec![literal("ab")], vec![rule("a")], vec![]]);
+
+        let ends: Vec<usize> = grammar.ends("0", "babb", 1).into_iter().collect();
+        assert_eq!(vec![1, 2, 3], ends);
+        assert!(grammar.ends("0", "babb", 4).contains(&4));
+        assert!(grammar.ends("missing", "a", 0).is_empty());
+    }
+
+    #[test]
+    fn test_right_recursion() {
+        let mut grammar = letters();
+        // a^n b^n for any n > 0
+        grammar.insert(
+            "0",
+            vec![
+                vec![rule("a"), rule("b")],
+                vec![rule("a"), rule("0"), rule("b")],
+            ],
+        );
+
+        assert!(grammar.matches("0", "ab"));
+        assert!(grammar.matches("0", &format!("{}{}", "a".repeat(20), "b".repeat(20))));
+        assert!(!grammar.matches("0", &format!("{}{}", "a".repeat(20), "b".repeat(19))));
+        assert!(!grammar.matches("0", "abab"));
+    }
+
+    #[test]
+    fn test_left_recursion() {
+        let mut grammar = letters();
+        grammar.insert("0", vec![vec![rule("0"), rule("a")], vec![rule("b")]]);
+
+        assert!(grammar.matches("0", "b"));
+        assert!(grammar.matches("0", "baaaa"));
+        assert!(!grammar.matches("0", "aaaa"));
+        assert!(!grammar.matches("0", "baab"));
+
+        // Two rules that start with each other, and one that only refers to itself.
+        grammar.insert("1", vec![vec![rule("2"), rule("a")], vec![rule("b")]]);
+        grammar.insert("2", vec![vec![rule("1"), rule("b")], vec![rule("3")]]);
+        grammar.insert("3", vec![vec![rule("3")]]);
+
+        assert!(grammar.matches("1", "bbaba"));
+        assert!(!grammar.matches("1", "bbab"));
+        assert!(grammar.matches("2", "bbab"));
+        assert!(!grammar.matches("3", ""));
+    }
+
+    #[test]
+    fn test_nested_recursion() {
+        let mut grammar = letters();
+        // Balanced brackets, with "a" for '(' and "b" for ')'.
+        grammar.insert(
+            "0",
+            vec![
+                vec![rule("0"), rule("0")],
+                vec![rule("a"), rule("0"), rule("b")],
+                vec![rule("a"), rule("b")],
+            ],
+        );
+
+        assert!(grammar.matches("0", "aabbab"));
+        assert!(grammar.matches("0", "aababbaabb"));
+        assert!(!grammar.matches("0", "aabbb"));
+        assert!(!grammar.matches("0", "abba"));
+    }
+
+    #[test]
+    fn test_long_message() {
+        let mut grammar = letters();
+        // Every string of a's and b's, which has exponentially many ways to split up.
+        grammar.insert(
+            "0",
+            vec![vec![rule("0"), rule("0")], vec![rule("a")], vec![rule("b")]],
+        );
+
+        let message = "ab".repeat(50);
+        assert!(grammar.matches("0", &message));
+        assert!(!grammar.matches("0", &format!("{}c", message)));
+    }
+}
@@ -0,0 +1,443 @@
+use error::{Error, Result};
+use std::fmt;
+
+/// Row and column offsets of the 4 orthogonal neighbours.
+pub const DIRECTIONS_4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Row and column offsets of the 8 neighbours, diagonals included.
+pub const DIRECTIONS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular map of cells, stored row by row. Positions are `(row, col)` pairs.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "cells don't fill the grid");
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(|(row, col)| f(row, col))
+            .collect();
+        Grid::new(width, height, cells)
+    }
+
+    /// Parses a character map, one row per line. `parse_cell` returns `None` for characters
+    /// that aren't allowed, which are reported as "expected `expected`".
+    pub fn parse<F: Fn(char) -> Option<T>>(
+        input: &str,
+        parse_cell: F,
+        expected: &str,
+    ) -> Result<Self> {
+        Grid::parse_within(input, input, parse_cell, expected)
+    }
+
+    /// Like `parse`, for a map that is a slice of a larger `source`, which errors point into.
+    pub fn parse_within<F: Fn(char) -> Option<T>>(
+        source: &str,
+        input: &str,
+        parse_cell: F,
+        expected: &str,
+    ) -> Result<Self> {
+        let width = match input.lines().next() {
+            Some(line) if !line.is_empty() => line.chars().count(),
+            _ => return Err(Error::at(source, input, "expected a non-empty map")),
+        };
+
+        let mut cells = Vec::new();
+        let mut height = 0;
+        for line in input.lines() {
+            for (idx, c) in line.char_indices() {
+                match parse_cell(c) {
+                    Some(cell) => cells.push(cell),
+                    None => {
+                        let message = format!("expected {}, found '{}'", expected, c);
+                        return Err(Error::at(source, &line[idx..], message));
+                    }
+                }
+            }
+            if cells.len() != (height + 1) * width {
+                let message = format!(
+                    "expected a row of width {}, found {}",
+                    width,
+                    line.chars().count()
+                );
+                return Err(Error::at(source, line, message));
+            }
+            height += 1;
+        }
+
+        Ok(Grid::new(width, height, cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.height && col < self.width {
+            self.cells.get(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.height && col < self.width {
+            self.cells.get_mut(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    /// Like `get`, for positions that may have gone past the top or left edge.
+    pub fn get_signed(&self, row: isize, col: isize) -> Option<&T> {
+        if row < 0 || col < 0 {
+            return None;
+        }
+        self.get(row as usize, col as usize)
+    }
+
+    /// Treats the grid as repeating forever in every direction.
+    pub fn get_wrapping(&self, row: usize, col: usize) -> &T {
+        &self.cells[(row % self.height) * self.width + col % self.width]
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |row| self.row(row))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        // A grid with no columns has no cells either, so any non-zero step will do.
+        self.cells.iter().skip(col).step_by(self.width.max(1))
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(idx, cell)| ((idx / width, idx % width), cell))
+    }
+
+    /// The cells next to `(row, col)` in each of `directions` that are inside the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        row: usize,
+        col: usize,
+        directions: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+        directions.iter().filter_map(move |(d_row, d_col)| {
+            let (n_row, n_col) = (row as isize + d_row, col as isize + d_col);
+            self.get_signed(n_row, n_col)
+                .map(|cell| ((n_row as usize, n_col as usize), cell))
+        })
+    }
+
+    pub fn neighbours_4(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.neighbours(row, col, &DIRECTIONS_4)
+    }
+
+    pub fn neighbours_8(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.neighbours(row, col, &DIRECTIONS_8)
+    }
+
+    /// The cells from `(row, col)`, not included, to the edge of the grid in one direction.
+    pub fn ray(
+        &self,
+        row: usize,
+        col: usize,
+        (d_row, d_col): (isize, isize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        (1..).map_while(move |distance| {
+            let (n_row, n_col) = (
+                row as isize + d_row * distance,
+                col as isize + d_col * distance,
+            );
+            self.get_signed(n_row, n_col)
+                .map(|cell| ((n_row as usize, n_col as usize), cell))
+        })
+    }
+
+    /// The first cell seen from `(row, col)` in each of the 8 directions, looking through the
+    /// cells for which `is_transparent` holds.
+    pub fn line_of_sight<'a, F: Fn(&T) -> bool + 'a>(
+        &'a self,
+        row: usize,
+        col: usize,
+        is_transparent: F,
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+        DIRECTIONS_8.iter().filter_map(move |direction| {
+            self.ray(row, col, *direction)
+                .find(|(_, cell)| !is_transparent(cell))
+        })
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Like `map`, with the position of each cell.
+    pub fn map_positions<U, F: FnMut((usize, usize), &T) -> U>(&self, mut f: F) -> Grid<U> {
+        Grid::new(
+            self.width,
+            self.height,
+            self.iter()
+                .map(|(position, cell)| f(position, cell))
+                .collect(),
+        )
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Grid::new(width, height, vec![value; width * height])
+    }
+
+    /// Swaps rows and columns, mirroring the grid along its main diagonal.
+    pub fn transpose(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |row, col| {
+            self.get(col, row).unwrap().clone()
+        })
+    }
+
+    pub fn rotate_clockwise(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |row, col| {
+            self.get(self.height - 1 - col, row).unwrap().clone()
+        })
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |row, col| {
+            self.get(col, self.width - 1 - row).unwrap().clone()
+        })
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |row, col| {
+            self.get(row, self.width - 1 - col).unwrap().clone()
+        })
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |row, col| {
+            self.get(self.height - 1 - row, col).unwrap().clone()
+        })
+    }
+
+    /// The 8 ways of turning the grid and flipping it over, starting with the grid as it is.
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut orientations = Vec::with_capacity(8);
+        let mut rotated = self.clone();
+        for _ in 0..4 {
+            let flipped = rotated.flip_horizontal();
+            let next = rotated.rotate_clockwise();
+            orientations.push(rotated);
+            orientations.push(flipped);
+            rotated = next;
+        }
+        orientations
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, row) in self.rows().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(input: &str) -> Grid<char> {
+        Grid::parse(input, Some, "a character").unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = grid("ab\ncd\nef");
+
+        assert_eq!(2, grid.width());
+        assert_eq!(3, grid.height());
+        assert_eq!(Some(&'d'), grid.get(1, 1));
+        assert_eq!(vec![&'a', &'c', &'e'], grid.column(0).collect::<Vec<_>>());
+        assert_eq!(&['e', 'f'], grid.row(2));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse_cell = |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        };
+        assert_eq!(
+            Err(Error::parse(2, 2, "expected '#' or '.', found 'x'")),
+            Grid::parse("#.\n.x", parse_cell, "'#' or '.'")
+        );
+        assert_eq!(
+            Err(Error::parse(2, 1, "expected a row of width 2, found 3")),
+            Grid::parse("#.\n..#", parse_cell, "'#' or '.'")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 1, "expected a non-empty map")),
+            Grid::parse("", parse_cell, "'#' or '.'")
+        );
+
+        let source = "Tile 1:\n#.\n.x";
+        assert_eq!(
+            Err(Error::parse(3, 2, "expected '#' or '.', found 'x'")),
+            Grid::parse_within(source, &source[8..], parse_cell, "'#' or '.'")
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let grid = grid("ab\ncd");
+
+        assert_eq!(Some(&'b'), grid.get(0, 1));
+        assert_eq!(None, grid.get(0, 2));
+        assert_eq!(None, grid.get(2, 0));
+        assert_eq!(None, grid.get_signed(-1, 0));
+        assert_eq!(Some(&'c'), grid.get_signed(1, 0));
+        assert_eq!(&'d', grid.get_wrapping(3, 5));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = grid("abc\ndef\nghi");
+        let neighbours = |iter: Box<dyn Iterator<Item = ((usize, usize), &char)> + '_>| {
+            iter.map(|(_, c)| *c).collect::<String>()
+        };
+
+        assert_eq!("bfhd", neighbours(Box::new(grid.neighbours_4(1, 1))));
+        assert_eq!("bd", neighbours(Box::new(grid.neighbours_4(0, 0))));
+        assert_eq!("abcdfghi", neighbours(Box::new(grid.neighbours_8(1, 1))));
+        assert_eq!("efh", neighbours(Box::new(grid.neighbours_8(2, 2))));
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let grid = grid("#..#\n....\n#..L\n.#..");
+        let seen: Vec<(usize, usize)> = grid
+            .line_of_sight(2, 3, |c| *c == '.')
+            .map(|(position, _)| position)
+            .collect();
+
+        assert_eq!(vec![(0, 3), (2, 0)], seen);
+        assert_eq!(
+            vec![((1, 0), &'.'), ((0, 0), &'#')],
+            grid.ray(2, 0, (-1, 0)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = grid("abc\ndef");
+
+        assert_eq!("ad\nbe\ncf", grid.transpose().to_string());
+        assert_eq!("da\neb\nfc", grid.rotate_clockwise().to_string());
+        assert_eq!("cf\nbe\nad", grid.rotate_counterclockwise().to_string());
+        assert_eq!("cba\nfed", grid.flip_horizontal().to_string());
+        assert_eq!("def\nabc", grid.flip_vertical().to_string());
+        assert_eq!(
+            grid,
+            grid.rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise()
+                .rotate_clockwise()
+        );
+    }
+
+    #[test]
+    fn test_orientations() {
+        let grid = grid("ab\ncd");
+        let orientations: Vec<String> = grid
+            .orientations()
+            .iter()
+            .map(|orientation| orientation.to_string().replace('\n', "/"))
+            .collect();
+
+        assert_eq!(
+            vec!["ab/cd", "ba/dc", "ca/db", "ac/bd", "dc/ba", "cd/ab", "bd/ac", "db/ca"],
+            orientations
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let grid = grid("ab\ncd");
+
+        assert_eq!("AB\nCD", grid.map(|c| c.to_ascii_uppercase()).to_string());
+        assert_eq!(
+            "01\n12",
+            grid.map_positions(|(row, col), _| row + col).to_string()
+        );
+        assert_eq!("xx\nxx", Grid::filled(2, 2, 'x').to_string());
+    }
+
+    #[test]
+    fn test_empty() {
+        let grid: Grid<char> = Grid::new(0, 3, Vec::new());
+
+        assert_eq!(
+            vec![0, 0, 0],
+            grid.rows().map(|row| row.len()).collect::<Vec<_>>()
+        );
+        assert_eq!(0, grid.column(0).count());
+        assert_eq!(None, grid.get(0, 0));
+        assert_eq!(0, Grid::<char>::new(2, 0, Vec::new()).rows().count());
+    }
+}
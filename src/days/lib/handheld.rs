@@ -0,0 +1,573 @@
+use super::control_flow::ControlFlowGraph;
+use error::{Error, Result};
+use std::collections::HashSet;
+use std::fmt;
+
+/// An instruction of the handheld game console, with its argument.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Instruction {
+    /// Does nothing.
+    Nop(i64),
+    /// Adds its argument to the accumulator.
+    Acc(i64),
+    /// Moves the program counter by its argument.
+    Jmp(i64),
+}
+
+impl Instruction {
+    pub fn new(mnemonic: &str, argument: i64) -> Option<Self> {
+        match mnemonic {
+            "nop" => Some(Instruction::Nop(argument)),
+            "acc" => Some(Instruction::Acc(argument)),
+            "jmp" => Some(Instruction::Jmp(argument)),
+            _ => None,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Nop(_) => "nop",
+            Instruction::Acc(_) => "acc",
+            Instruction::Jmp(_) => "jmp",
+        }
+    }
+
+    pub fn argument(&self) -> i64 {
+        match self {
+            Instruction::Nop(argument)
+            | Instruction::Acc(argument)
+            | Instruction::Jmp(argument) => *argument,
+        }
+    }
+
+    /// The same operation with a different argument.
+    pub fn with_argument(&self, argument: i64) -> Instruction {
+        match self {
+            Instruction::Nop(_) => Instruction::Nop(argument),
+            Instruction::Acc(_) => Instruction::Acc(argument),
+            Instruction::Jmp(_) => Instruction::Jmp(argument),
+        }
+    }
+
+    /// The instruction with `jmp` and `nop` swapped, for the instructions that can be swapped.
+    pub fn flipped(&self) -> Option<Instruction> {
+        match *self {
+            Instruction::Nop(argument) => Some(Instruction::Jmp(argument)),
+            Instruction::Jmp(argument) => Some(Instruction::Nop(argument)),
+            Instruction::Acc(_) => None,
+        }
+    }
+
+    /// Where the program counter goes after running this instruction at `program_counter`, if
+    /// that's within a program of `len` instructions or just past its end.
+    pub fn successor(&self, program_counter: usize, len: usize) -> Option<usize> {
+        let target = match self {
+            Instruction::Nop(_) | Instruction::Acc(_) => program_counter as i64 + 1,
+            Instruction::Jmp(argument) => (program_counter as i64).checked_add(*argument)?,
+        };
+        if (0..=len as i64).contains(&target) {
+            Some(target as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:+}", self.mnemonic(), self.argument())
+    }
+}
+
+/// Parses one instruction per line, like `acc +1`.
+pub fn parse_program(program: &str) -> Result<Vec<Instruction>> {
+    program
+        .lines()
+        .map(|line| {
+            let mut tokens = line.split_whitespace();
+            let mnemonic = match tokens.next() {
+                Some(token) => token,
+                None => return Err(Error::at(program, line, "expected an operation")),
+            };
+            let argument = match tokens.next() {
+                Some(token) => super::parse_token(program, token, "an integer argument")?,
+                None => return Err(Error::at(program, line, "expected an argument")),
+            };
+            if let Some(token) = tokens.next() {
+                return Err(Error::at(
+                    program,
+                    token,
+                    format!("expected the end of the line, found '{}'", token),
+                ));
+            }
+
+            Instruction::new(mnemonic, argument).ok_or_else(|| {
+                Error::at(
+                    program,
+                    mnemonic,
+                    format!("expected 'nop', 'acc' or 'jmp', found '{}'", mnemonic),
+                )
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct State {
+    pub accumulator: i64,
+    pub program_counter: usize,
+}
+
+/// Why the console stopped running.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Halt {
+    /// The program counter moved to just past the last instruction.
+    Terminated,
+    /// The instruction at `program_counter` was about to run a second time.
+    Loop { program_counter: usize },
+    /// The jump at `program_counter` went to `target`, outside of the program.
+    OutOfBounds { program_counter: usize, target: i64 },
+    /// The instruction at `program_counter` overflowed the accumulator or the program counter.
+    Overflow { program_counter: usize },
+    /// The program counter reached a breakpoint.
+    Breakpoint { program_counter: usize },
+    /// The condition given to `run_until` held.
+    Stopped { program_counter: usize },
+}
+
+impl Halt {
+    /// Whether the program can't go any further, as opposed to having been paused.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Halt::Breakpoint { .. } | Halt::Stopped { .. })
+    }
+}
+
+/// An executed instruction, and the accumulator after it ran.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Step {
+    pub program_counter: usize,
+    pub instruction: Instruction,
+    pub accumulator: i64,
+}
+
+/// Runs a program one instruction at a time, stopping before any instruction would run twice.
+#[derive(Debug, Clone)]
+pub struct Console<'a> {
+    program: &'a [Instruction],
+    state: State,
+    visited: Vec<bool>,
+    halt: Option<Halt>,
+    breakpoints: HashSet<usize>,
+    trace: Option<Vec<Step>>,
+}
+
+impl<'a> Console<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
+        Console {
+            program,
+            state: State::default(),
+            visited: vec![false; program.len()],
+            halt: None,
+            breakpoints: HashSet::new(),
+            trace: None,
+        }
+    }
+
+    pub fn program(&self) -> &'a [Instruction] {
+        self.program
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn accumulator(&self) -> i64 {
+        self.state.accumulator
+    }
+
+    pub fn program_counter(&self) -> usize {
+        self.state.program_counter
+    }
+
+    /// Starts the program again, keeping the breakpoints and whether it's traced.
+    pub fn reset(&mut self) {
+        self.state = State::default();
+        self.visited.iter_mut().for_each(|visited| *visited = false);
+        self.halt = None;
+        if let Some(trace) = &mut self.trace {
+            trace.clear();
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, program_counter: usize) {
+        self.breakpoints.insert(program_counter);
+    }
+
+    pub fn remove_breakpoint(&mut self, program_counter: usize) {
+        self.breakpoints.remove(&program_counter);
+    }
+
+    /// Starts or stops recording every executed instruction.
+    pub fn set_tracing(&mut self, tracing: bool) {
+        self.trace = if tracing { Some(Vec::new()) } else { None };
+    }
+
+    /// The instructions executed since tracing started.
+    pub fn trace(&self) -> &[Step] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    /// Executes the next instruction, or returns why there isn't one to execute. Once the
+    /// program has halted for good it stays halted.
+    pub fn step(&mut self) -> Option<Halt> {
+        if let Some(halt) = self.halt {
+            return Some(halt);
+        }
+
+        let program_counter = self.state.program_counter;
+        let instruction = match self.program.get(program_counter) {
+            Some(instruction) => *instruction,
+            None => return self.halt(Halt::Terminated),
+        };
+        if self.visited[program_counter] {
+            return self.halt(Halt::Loop { program_counter });
+        }
+
+        let overflow = Halt::Overflow { program_counter };
+        let state = match instruction {
+            Instruction::Nop(_) => State {
+                program_counter: program_counter + 1,
+                ..self.state
+            },
+            Instruction::Acc(argument) => match self.state.accumulator.checked_add(argument) {
+                Some(accumulator) => State {
+                    accumulator,
+                    program_counter: program_counter + 1,
+                },
+                None => return self.halt(overflow),
+            },
+            Instruction::Jmp(argument) => {
+                let target = match (program_counter as i64).checked_add(argument) {
+                    Some(target) => target,
+                    None => return self.halt(overflow),
+                };
+                if target < 0 || target > self.program.len() as i64 {
+                    return self.halt(Halt::OutOfBounds {
+                        program_counter,
+                        target,
+                    });
+                }
+                State {
+                    program_counter: target as usize,
+                    ..self.state
+                }
+            }
+        };
+
+        self.visited[program_counter] = true;
+        self.state = state;
+        if let Some(trace) = &mut self.trace {
+            trace.push(Step {
+                program_counter,
+                instruction,
+                accumulator: state.accumulator,
+            });
+        }
+        None
+    }
+
+    fn halt(&mut self, halt: Halt) -> Option<Halt> {
+        self.halt = Some(halt);
+        Some(halt)
+    }
+
+    /// Runs until the program halts or reaches a breakpoint. A breakpoint on the instruction it
+    /// starts from doesn't stop it, so that calling `run` again carries on past it.
+    pub fn run(&mut self) -> Halt {
+        self.run_until(|_, _| false)
+    }
+
+    /// Like `run`, but also stops before executing an instruction for which `condition` holds,
+    /// other than the first one.
+    pub fn run_until<F>(&mut self, mut condition: F) -> Halt
+    where
+        F: FnMut(&State, &Instruction) -> bool,
+    {
+        let mut first = true;
+        loop {
+            let program_counter = self.state.program_counter;
+            if !first && self.halt.is_none() {
+                if self.breakpoints.contains(&program_counter) {
+                    return Halt::Breakpoint { program_counter };
+                }
+                if let Some(instruction) = self.program.get(program_counter) {
+                    if condition(&self.state, instruction) {
+                        return Halt::Stopped { program_counter };
+                    }
+                }
+            }
+            first = false;
+
+            if let Some(halt) = self.step() {
+                return halt;
+            }
+        }
+    }
+}
+
+/// The instructions of a program that doesn't terminate which would make it terminate if their
+/// `jmp` or `nop` were swapped, in the order the program runs them. Only the instructions it runs,
+/// up to and including the one it halts on, can matter, and none of them reaches the end of the
+/// program, so swapping one of them terminates exactly when it now leads to an instruction that
+/// does. This takes linear time.
+pub fn repairs(program: &[Instruction]) -> Vec<usize> {
+    let mut console = Console::new(program);
+    console.set_tracing(true);
+    let halted_at = match console.run() {
+        Halt::Terminated => return Vec::new(),
+        Halt::OutOfBounds {
+            program_counter, ..
+        }
+        | Halt::Overflow { program_counter } => Some(program_counter),
+        _ => None,
+    };
+
+    let reaches = ControlFlowGraph::new(program).reaches_end();
+    console
+        .trace()
+        .iter()
+        .map(|step| step.program_counter)
+        .chain(halted_at)
+        .filter(|program_counter| {
+            program[*program_counter]
+                .flipped()
+                .and_then(|flipped| flipped.successor(*program_counter, program.len()))
+                .is_some_and(|successor| reaches[successor])
+        })
+        .collect()
+}
+
+/// The example program from day 8.
+#[cfg(test)]
+pub(crate) const TEST_CASE: &str = "nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_program() {
+        assert_eq!(
+            Ok(vec![
+                Instruction::Nop(0),
+                Instruction::Acc(1),
+                Instruction::Jmp(-4)
+            ]),
+            parse_program("nop +0\nacc +1\njmp -4")
+        );
+    }
+
+    #[test]
+    fn test_parse_program_errors() {
+        assert_eq!(
+            Err(Error::parse(
+                2,
+                1,
+                "expected 'nop', 'acc' or 'jmp', found 'add'"
+            )),
+            parse_program("nop +0\nadd +1")
+        );
+        assert_eq!(
+            Err(Error::parse(
+                1,
+                5,
+                "expected an integer argument, found 'x1'"
+            )),
+            parse_program("acc x1")
+        );
+        assert_eq!(
+            Err(Error::parse(1, 1, "expected an argument")),
+            parse_program("jmp")
+        );
+        assert_eq!(
+            Err(Error::parse(
+                2,
+                8,
+                "expected the end of the line, found 'extra'"
+            )),
+            parse_program("nop +0\njmp +1 extra")
+        );
+    }
+
+    #[test]
+    fn test_instruction() {
+        assert_eq!(Some(Instruction::Nop(3)), Instruction::new("nop", 3));
+        assert_eq!(Some(Instruction::Acc(-1)), Instruction::new("acc", -1));
+        assert_eq!(Some(Instruction::Jmp(0)), Instruction::new("jmp", 0));
+        assert_eq!(None, Instruction::new("abc", 0));
+        assert_eq!("jmp -4", Instruction::Jmp(-4).to_string());
+        assert_eq!("acc +0", Instruction::Acc(0).to_string());
+        assert_eq!(Instruction::Jmp(-2), Instruction::Jmp(5).with_argument(-2));
+    }
+
+    #[test]
+    fn test_flipped() {
+        assert_eq!(Some(Instruction::Jmp(3)), Instruction::Nop(3).flipped());
+        assert_eq!(Some(Instruction::Nop(-3)), Instruction::Jmp(-3).flipped());
+        assert_eq!(None, Instruction::Acc(1).flipped());
+    }
+
+    #[test]
+    fn test_successor() {
+        assert_eq!(Some(3), Instruction::Acc(-5).successor(2, 4));
+        assert_eq!(Some(4), Instruction::Nop(-5).successor(3, 4));
+        assert_eq!(Some(0), Instruction::Jmp(-2).successor(2, 4));
+        assert_eq!(None, Instruction::Jmp(-3).successor(2, 4));
+        assert_eq!(None, Instruction::Jmp(3).successor(2, 4));
+        assert_eq!(None, Instruction::Jmp(i64::MAX).successor(2, 4));
+    }
+
+    #[test]
+    fn test_repairs() {
+        assert_eq!(vec![7], repairs(&parse_program(TEST_CASE).unwrap()));
+
+        let program = parse_program("nop +2\njmp +0\nacc +1").unwrap();
+        assert_eq!(vec![0, 1], repairs(&program));
+        assert_eq!(
+            Vec::<usize>::new(),
+            repairs(&parse_program("acc +1\njmp +1").unwrap())
+        );
+        assert_eq!(
+            Vec::<usize>::new(),
+            repairs(&parse_program("jmp +0\njmp +0").unwrap())
+        );
+
+        // The jump that leaves the program never finishes running, but swapping it still helps.
+        assert_eq!(vec![0], repairs(&parse_program("jmp +5\nacc +1").unwrap()));
+        assert_eq!(
+            vec![1],
+            repairs(&parse_program("acc +1\njmp +9223372036854775807").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_step() {
+        let program = [Instruction::Acc(10), Instruction::Jmp(-1)];
+        let mut console = Console::new(&program);
+
+        assert_eq!(None, console.step());
+        assert_eq!(
+            State {
+                accumulator: 10,
+                program_counter: 1
+            },
+            console.state()
+        );
+        assert_eq!(None, console.step());
+        assert_eq!(0, console.program_counter());
+        assert_eq!(Some(Halt::Loop { program_counter: 0 }), console.step());
+        assert_eq!(10, console.accumulator());
+    }
+
+    #[test]
+    fn test_run() {
+        let program = parse_program(TEST_CASE).unwrap();
+        let mut console = Console::new(&program);
+        assert_eq!(Halt::Loop { program_counter: 1 }, console.run());
+        assert_eq!(5, console.accumulator());
+
+        let program = parse_program("acc +2\nnop -1\njmp +1\nacc +3").unwrap();
+        let mut console = Console::new(&program);
+        assert_eq!(Halt::Terminated, console.run());
+        assert_eq!(5, console.accumulator());
+        assert_eq!(Halt::Terminated, console.run());
+    }
+
+    #[test]
+    fn test_run_out_of_bounds() {
+        let program = [Instruction::Nop(0), Instruction::Jmp(-2)];
+        let mut console = Console::new(&program);
+        let halt = Halt::OutOfBounds {
+            program_counter: 1,
+            target: -1,
+        };
+        assert_eq!(halt, console.run());
+        assert_eq!(1, console.program_counter());
+        assert_eq!(Some(halt), console.step());
+
+        let program = [Instruction::Nop(0), Instruction::Jmp(i64::MAX)];
+        assert_eq!(
+            Halt::Overflow { program_counter: 1 },
+            Console::new(&program).run()
+        );
+        let program = [Instruction::Acc(i64::MAX), Instruction::Acc(1)];
+        assert_eq!(
+            Halt::Overflow { program_counter: 1 },
+            Console::new(&program).run()
+        );
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let program = parse_program(TEST_CASE).unwrap();
+        let mut console = Console::new(&program);
+        console.add_breakpoint(3);
+        console.add_breakpoint(4);
+
+        assert_eq!(Halt::Breakpoint { program_counter: 3 }, console.run());
+        assert_eq!(Halt::Breakpoint { program_counter: 4 }, console.run());
+        console.remove_breakpoint(3);
+        assert_eq!(Halt::Loop { program_counter: 1 }, console.run());
+        assert!(console.run().is_final());
+
+        console.remove_breakpoint(4);
+        console.reset();
+        assert_eq!(
+            Halt::Stopped { program_counter: 2 },
+            console.run_until(|_, instruction| instruction.argument() == 4)
+        );
+        assert_eq!(
+            Halt::Stopped { program_counter: 4 },
+            console.run_until(|state, _| state.accumulator > 4)
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let program = parse_program(TEST_CASE).unwrap();
+        let mut console = Console::new(&program);
+        console.set_tracing(true);
+        console.run();
+
+        assert_eq!(
+            vec![0, 1, 2, 6, 7, 3, 4],
+            console
+                .trace()
+                .iter()
+                .map(|step| step.program_counter)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Step {
+                program_counter: 3,
+                instruction: Instruction::Acc(3),
+                accumulator: 5
+            },
+            console.trace()[5]
+        );
+
+        console.reset();
+        assert!(console.trace().is_empty());
+        console.set_tracing(false);
+        console.run();
+        assert!(console.trace().is_empty());
+    }
+}
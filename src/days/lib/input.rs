@@ -0,0 +1,194 @@
+use error::{Error, Result};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Overrides the directory the puzzle inputs are read from.
+pub const INPUTS_DIR_VAR: &str = "AOC_INPUTS_DIR";
+pub const DEFAULT_INPUTS_DIR: &str = "inputs";
+
+/// Reads a file, normalising its line endings.
+pub fn read_inputs<P: AsRef<Path>>(filename: P) -> Result<String> {
+    let filename = filename.as_ref();
+    fs::read_to_string(filename)
+        .map(|inputs| normalise_input(&inputs))
+        .map_err(|error| Error::io(filename.display().to_string(), &error))
+}
+
+/// Turns CRLF line endings into LF and drops trailing line breaks, so every day sees the
+/// same text whatever editor or platform saved the input.
+pub fn normalise_input(input: &str) -> String {
+    input
+        .replace("\r\n", "\n")
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum InputKind {
+    /// `dayN.txt`, the personal puzzle input.
+    Real,
+    /// `dayN.example.txt`, the example from the puzzle text.
+    Example,
+}
+
+/// Where a day's puzzle input comes from. `-` stands for stdin.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InputSource {
+    /// The day's own input, looked up by an `InputProvider`.
+    Puzzle(InputKind),
+    File(String),
+    Stdin,
+}
+
+impl InputSource {
+    pub fn from_arg(arg: &str) -> Self {
+        match arg {
+            "-" => InputSource::Stdin,
+            path => InputSource::File(path.to_string()),
+        }
+    }
+
+    pub fn read(&self, day: usize, provider: &InputProvider) -> Result<String> {
+        match self {
+            InputSource::Puzzle(kind) => provider.read(day, *kind),
+            InputSource::File(filename) => read_inputs(filename),
+            InputSource::Stdin => {
+                let mut inputs = String::new();
+                io::stdin()
+                    .read_to_string(&mut inputs)
+                    .map_err(|error| Error::io("stdin", &error))?;
+                Ok(normalise_input(&inputs))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for InputSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputSource::Puzzle(InputKind::Real) => write!(f, "puzzle input"),
+            InputSource::Puzzle(InputKind::Example) => write!(f, "example input"),
+            InputSource::File(filename) => write!(f, "{}", filename),
+            InputSource::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+/// Finds the puzzle inputs in a directory and keeps each one in memory once read.
+#[derive(Debug)]
+pub struct InputProvider {
+    dir: PathBuf,
+    cache: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl InputProvider {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        InputProvider {
+            dir: dir.into(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Uses `dir` when given, then `AOC_INPUTS_DIR`, then `inputs` in the working directory,
+    /// and finally the `inputs` directory of this repository.
+    pub fn locate(dir: Option<&str>) -> Self {
+        if let Some(dir) = dir {
+            return InputProvider::new(dir);
+        }
+        if let Some(dir) = env::var_os(INPUTS_DIR_VAR) {
+            return InputProvider::new(dir);
+        }
+        if Path::new(DEFAULT_INPUTS_DIR).is_dir() {
+            return InputProvider::new(DEFAULT_INPUTS_DIR);
+        }
+        InputProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_INPUTS_DIR))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self, day: usize, kind: InputKind) -> PathBuf {
+        let filename = match kind {
+            InputKind::Real => format!("day{}.txt", day),
+            InputKind::Example => format!("day{}.example.txt", day),
+        };
+        self.dir.join(filename)
+    }
+
+    pub fn read(&self, day: usize, kind: InputKind) -> Result<String> {
+        let path = self.path(day, kind);
+        if let Some(inputs) = self.cache.lock().unwrap().get(&path) {
+            return Ok(inputs.clone());
+        }
+        if !path.is_file() {
+            return Err(Error::missing_input(day, path.display().to_string()));
+        }
+
+        let inputs = read_inputs(&path)?;
+        self.cache.lock().unwrap().insert(path, inputs.clone());
+        Ok(inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalise_input() {
+        assert_eq!("1\n2\n\n3", normalise_input("1\r\n2\r\n\r\n3\r\n\r\n"));
+        assert_eq!("1\n2", normalise_input("1\n2"));
+    }
+
+    #[test]
+    fn test_read_inputs_missing_file() {
+        assert!(matches!(
+            read_inputs("inputs/missing.txt"),
+            Err(Error::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_input_source_from_arg() {
+        assert_eq!(InputSource::Stdin, InputSource::from_arg("-"));
+        assert_eq!(
+            InputSource::File("inputs/day1.txt".to_string()),
+            InputSource::from_arg("inputs/day1.txt")
+        );
+    }
+
+    #[test]
+    fn test_provider_path() {
+        let provider = InputProvider::new("some/dir");
+        assert_eq!(
+            Path::new("some/dir/day7.txt"),
+            provider.path(7, InputKind::Real)
+        );
+        assert_eq!(
+            Path::new("some/dir/day7.example.txt"),
+            provider.path(7, InputKind::Example)
+        );
+    }
+
+    #[test]
+    fn test_provider_read() {
+        let provider = InputProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs"));
+        let inputs = provider.read(20, InputKind::Real).unwrap();
+
+        assert!(inputs.starts_with("Tile "));
+        assert!(!inputs.ends_with('\n'));
+        assert_eq!(Ok(inputs), provider.read(20, InputKind::Real));
+        assert_eq!(
+            Err(Error::missing_input(
+                99,
+                provider.path(99, InputKind::Real).display().to_string()
+            )),
+            provider.read(99, InputKind::Real)
+        );
+    }
+}
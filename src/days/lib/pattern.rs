@@ -0,0 +1,195 @@
+use super::grid::Grid;
+use error::{Error, Result};
+use std::collections::HashSet;
+
+/// A picture to look for in a grid of characters, with wildcard cells that match anything.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pattern {
+    cells: Grid<Option<char>>,
+}
+
+/// Where a pattern was found: the index of the orientation it was found in, the position of its
+/// top left corner and the cells it covers, which leave out its wildcards.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Match {
+    pub orientation: usize,
+    pub position: (usize, usize),
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Pattern {
+    /// Parses a pattern, one row per line, where `wildcard` matches any cell. Short rows are
+    /// padded with wildcards.
+    pub fn parse(pattern: &str, wildcard: char) -> Result<Self> {
+        let width = pattern
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Err(Error::input("expected a non-empty pattern"));
+        }
+
+        let rows: Vec<Vec<char>> = pattern.lines().map(|line| line.chars().collect()).collect();
+        let cells = Grid::from_fn(width, rows.len(), |row, col| {
+            rows[row].get(col).copied().filter(|c| *c != wildcard)
+        });
+        Ok(Pattern { cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    /// The distinct ways of turning and flipping the pattern over, starting with the pattern as
+    /// it is.
+    pub fn orientations(&self) -> Vec<Pattern> {
+        let mut orientations: Vec<Pattern> = Vec::new();
+        for cells in self.cells.orientations() {
+            let pattern = Pattern { cells };
+            if !orientations.contains(&pattern) {
+                orientations.push(pattern);
+            }
+        }
+        orientations
+    }
+
+    fn covered_cells(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells
+            .iter()
+            .filter(|(_, cell)| cell.is_some())
+            .map(move |((d_row, d_col), _)| (row + d_row, col + d_col))
+    }
+
+    pub fn matches_at(&self, grid: &Grid<char>, row: usize, col: usize) -> bool {
+        self.cells.iter().all(|((d_row, d_col), cell)| match cell {
+            Some(c) => grid.get(row + d_row, col + d_col) == Some(c),
+            None => row + d_row < grid.height() && col + d_col < grid.width(),
+        })
+    }
+
+    /// The top left corner of every place the pattern is found, overlapping or not, row by row.
+    pub fn find(&self, grid: &Grid<char>) -> Vec<(usize, usize)> {
+        if self.height() > grid.height() || self.width() > grid.width() {
+            return Vec::new();
+        }
+
+        (0..=grid.height() - self.height())
+            .flat_map(|row| (0..=grid.width() - self.width()).map(move |col| (row, col)))
+            .filter(|(row, col)| self.matches_at(grid, *row, *col))
+            .collect()
+    }
+
+    /// Every match of any orientation of the pattern, in the order of `orientations`.
+    pub fn find_in_any_orientation(&self, grid: &Grid<char>) -> Vec<Match> {
+        self.orientations()
+            .iter()
+            .enumerate()
+            .flat_map(|(orientation, pattern)| {
+                pattern.find(grid).into_iter().map(move |(row, col)| Match {
+                    orientation,
+                    position: (row, col),
+                    cells: pattern.covered_cells(row, col).collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Replaces the cells covered by any of `matches` with `replacement`.
+pub fn mask(grid: &Grid<char>, matches: &[Match], replacement: char) -> Grid<char> {
+    let covered: HashSet<(usize, usize)> = matches
+        .iter()
+        .flat_map(|found| found.cells.iter().copied())
+        .collect();
+    grid.map_positions(|position, c| {
+        if covered.contains(&position) {
+            replacement
+        } else {
+            *c
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(input: &str) -> Grid<char> {
+        Grid::parse(input, Some, "a cell").unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let pattern = Pattern::parse("#.\n ##", ' ').unwrap();
+
+        assert_eq!((3, 2), (pattern.width(), pattern.height()));
+        assert_eq!(
+            vec![Some('#'), Some('.'), None, None, Some('#'), Some('#')],
+            pattern.cells.cells().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Err(Error::input("expected a non-empty pattern")),
+            Pattern::parse("", ' ')
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let pattern = Pattern::parse("#?#", '?').unwrap();
+        let grid = grid("#.#.#\n###..\n.....");
+
+        assert!(pattern.matches_at(&grid, 0, 0));
+        assert!(!pattern.matches_at(&grid, 0, 3));
+        assert_eq!(vec![(0, 0), (0, 2), (1, 0)], pattern.find(&grid));
+        assert_eq!(
+            Vec::<(usize, usize)>::new(),
+            Pattern::parse("######", ' ').unwrap().find(&grid)
+        );
+    }
+
+    #[test]
+    fn test_orientations() {
+        assert_eq!(1, Pattern::parse("#", ' ').unwrap().orientations().len());
+        assert_eq!(2, Pattern::parse("##", ' ').unwrap().orientations().len());
+        assert_eq!(
+            4,
+            Pattern::parse("##\n#", ' ').unwrap().orientations().len()
+        );
+        assert_eq!(
+            8,
+            Pattern::parse("###\n#", ' ').unwrap().orientations().len()
+        );
+    }
+
+    #[test]
+    fn test_find_in_any_orientation_and_mask() {
+        let pattern = Pattern::parse("##\n #", ' ').unwrap();
+        let grid = grid("##..\n.#..\n...#\n..##");
+        let matches = pattern.find_in_any_orientation(&grid);
+
+        assert_eq!(
+            vec![
+                Match {
+                    orientation: 0,
+                    position: (0, 0),
+                    cells: vec![(0, 0), (0, 1), (1, 1)],
+                },
+                Match {
+                    orientation: 2,
+                    position: (2, 2),
+                    cells: vec![(2, 3), (3, 2), (3, 3)],
+                },
+            ],
+            matches
+        );
+        assert_eq!(
+            "OO..\n.O..\n...O\n..OO",
+            mask(&grid, &matches, 'O').to_string()
+        );
+    }
+}
@@ -0,0 +1,246 @@
+extern crate gif;
+use self::gif::{Encoder, Frame, Repeat};
+
+use super::grid::Grid;
+use error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many hundredths of a second each GIF frame is shown for.
+const GIF_FRAME_DELAY: u16 = 10;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AnimationFormat {
+    /// Every frame as text, one after the other.
+    Ascii,
+    /// A directory of PPM images, one per frame.
+    Ppm,
+    Gif,
+}
+
+/// The colour of each character of a frame, and how many pixels wide a character is drawn.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Palette {
+    colours: Vec<(char, [u8; 3])>,
+    background: [u8; 3],
+    scale: usize,
+}
+
+impl Palette {
+    /// Characters missing from `colours` are drawn in the background colour, black.
+    pub fn new(colours: &[(char, [u8; 3])], scale: usize) -> Self {
+        Palette {
+            colours: colours.to_vec(),
+            background: [0, 0, 0],
+            scale,
+        }
+    }
+
+    /// The position of `c` in the GIF colour table, where the background comes first.
+    fn index(&self, c: char) -> u8 {
+        self.colours
+            .iter()
+            .position(|(colour_char, _)| *colour_char == c)
+            .map_or(0, |idx| idx as u8 + 1)
+    }
+
+    fn colour(&self, c: char) -> [u8; 3] {
+        self.colours
+            .iter()
+            .find(|(colour_char, _)| *colour_char == c)
+            .map_or(self.background, |(_, colour)| *colour)
+    }
+
+    fn colour_table(&self) -> Vec<u8> {
+        let mut table = self.background.to_vec();
+        for (_, colour) in &self.colours {
+            table.extend_from_slice(colour);
+        }
+        table
+    }
+}
+
+/// Collects the frames of a simulation, one per step, to look at how it changes over time.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Recorder {
+    palette: Palette,
+    frames: Vec<Grid<char>>,
+}
+
+impl Recorder {
+    pub fn new(palette: Palette) -> Self {
+        Recorder {
+            palette,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, frame: Grid<char>) {
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[Grid<char>] {
+        &self.frames
+    }
+
+    pub fn to_ascii(&self) -> String {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(idx, frame)| format!("Frame {}:\n{}\n", idx, frame))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// A binary PPM image of one frame.
+    pub fn to_ppm(&self, frame: &Grid<char>) -> Vec<u8> {
+        let scale = self.palette.scale;
+        let mut image = format!(
+            "P6\n{} {}\n255\n",
+            frame.width() * scale,
+            frame.height() * scale
+        )
+        .into_bytes();
+
+        for row in frame.rows() {
+            for _ in 0..scale {
+                for c in row {
+                    let colour = self.palette.colour(*c);
+                    for _ in 0..scale {
+                        image.extend_from_slice(&colour);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    /// Writes `frame-000.ppm`, `frame-001.ppm`, ... into `dir`, creating it if needed.
+    pub fn write_ppm_frames(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir).map_err(|error| Error::io(dir.display().to_string(), &error))?;
+
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(idx, frame)| {
+                let path = dir.join(format!("frame-{:03}.ppm", idx));
+                fs::write(&path, self.to_ppm(frame))
+                    .map_err(|error| Error::io(path.display().to_string(), &error))?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// An animated GIF of every frame. Frames smaller than the largest one are drawn in its top
+    /// left corner.
+    pub fn to_gif(&self) -> Result<Vec<u8>> {
+        if self.palette.colours.len() > 255 {
+            return Err(Error::input("a GIF can't have more than 255 colours"));
+        }
+        let scale = self.palette.scale;
+        let width = self
+            .frames
+            .iter()
+            .map(|frame| frame.width())
+            .max()
+            .unwrap_or(1)
+            * scale;
+        let height = self
+            .frames
+            .iter()
+            .map(|frame| frame.height())
+            .max()
+            .unwrap_or(1)
+            * scale;
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(Error::input(format!(
+                "{}x{} pixels is too large for a GIF",
+                width, height
+            )));
+        }
+
+        let mut image = Vec::new();
+        {
+            let mut encoder = Encoder::new(
+                &mut image,
+                width as u16,
+                height as u16,
+                &self.palette.colour_table(),
+            )
+            .map_err(gif_error)?;
+            encoder.set_repeat(Repeat::Infinite).map_err(gif_error)?;
+
+            for frame in &self.frames {
+                let mut pixels = vec![0; width * height];
+                for ((row, col), c) in frame.iter() {
+                    let index = self.palette.index(*c);
+                    for d_row in 0..scale {
+                        let start = (row * scale + d_row) * width + col * scale;
+                        pixels[start..start + scale]
+                            .iter_mut()
+                            .for_each(|p| *p = index);
+                    }
+                }
+
+                let mut gif_frame =
+                    Frame::from_indexed_pixels(width as u16, height as u16, &pixels, None);
+                gif_frame.delay = GIF_FRAME_DELAY;
+                encoder.write_frame(&gif_frame).map_err(gif_error)?;
+            }
+        }
+        Ok(image)
+    }
+}
+
+fn gif_error(error: gif::EncodingError) -> Error {
+    Error::input(format!("couldn't encode the GIF: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder() -> Recorder {
+        let palette = Palette::new(&[('#', [255, 0, 0]), ('L', [0, 255, 0])], 2);
+        let mut recorder = Recorder::new(palette);
+        recorder.record(Grid::parse("#L\n..", Some, "a cell").unwrap());
+        recorder.record(Grid::parse("L#.\n#..\n...", Some, "a cell").unwrap());
+        recorder
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        assert_eq!(
+            "Frame 0:\n#L\n..\n\nFrame 1:\nL#.\n#..\n...\n",
+            recorder().to_ascii()
+        );
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let recorder = recorder();
+        let image = recorder.to_ppm(&recorder.frames()[0]);
+        let header = b"P6\n4 4\n255\n";
+
+        assert_eq!(header, &image[..header.len()]);
+        assert_eq!(header.len() + 4 * 4 * 3, image.len());
+        let pixels = &image[header.len()..];
+        assert_eq!(&[255, 0, 0, 255, 0, 0, 0, 255, 0, 0, 255, 0], &pixels[..12]);
+        assert_eq!(&pixels[..12], &pixels[12..24]);
+        assert!(pixels[24..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_to_gif() {
+        let image = recorder().to_gif().unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(&image[..]).unwrap();
+
+        assert_eq!((6, 6), (decoder.width(), decoder.height()));
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(GIF_FRAME_DELAY, frame.delay);
+            frames += 1;
+        }
+        assert_eq!(2, frames);
+    }
+}
@@ -0,0 +1,238 @@
+use super::handheld::{Console, Halt, Instruction, State};
+
+/// A change that can be made to a single instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Mutation {
+    /// Swaps `jmp` and `nop`.
+    SwapOperation,
+    /// Changes the sign of the argument.
+    NegateArgument,
+    /// Removes the instruction, so the ones after it move up.
+    Delete,
+}
+
+impl Mutation {
+    /// Whether the mutation changes `instruction` at all.
+    fn applies_to(&self, instruction: &Instruction) -> bool {
+        match self {
+            Mutation::SwapOperation => instruction.flipped().is_some(),
+            Mutation::NegateArgument => instruction.argument().checked_neg().unwrap_or(0) != 0,
+            Mutation::Delete => true,
+        }
+    }
+}
+
+/// What the program should do once it's been repaired.
+pub enum Goal {
+    /// Run to its end.
+    Terminates,
+    /// Halt, for whatever reason, with the accumulator at this value.
+    Accumulator(i64),
+    /// Any other condition on how the program ended.
+    Predicate(fn(&Halt, &State) -> bool),
+}
+
+impl Goal {
+    fn is_met(&self, halt: &Halt, state: &State) -> bool {
+        match self {
+            Goal::Terminates => *halt == Halt::Terminated,
+            Goal::Accumulator(accumulator) => state.accumulator == *accumulator,
+            Goal::Predicate(predicate) => predicate(halt, state),
+        }
+    }
+}
+
+/// A mutation of the instruction at `index` in the original program.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Edit {
+    pub index: usize,
+    pub mutation: Mutation,
+}
+
+/// Edits that make a program reach its goal, and how the edited program ended.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Repair {
+    pub edits: Vec<Edit>,
+    pub halt: Halt,
+    pub state: State,
+}
+
+/// The program with each of `edits` made to it. There should be at most one edit per
+/// instruction.
+pub fn apply(program: &[Instruction], edits: &[Edit]) -> Vec<Instruction> {
+    program
+        .iter()
+        .enumerate()
+        .filter_map(
+            |(idx, instruction)| match edits.iter().find(|edit| edit.index == idx) {
+                None => Some(*instruction),
+                Some(edit) => match edit.mutation {
+                    Mutation::SwapOperation => instruction.flipped(),
+                    Mutation::NegateArgument => {
+                        Some(instruction.with_argument(instruction.argument().wrapping_neg()))
+                    }
+                    Mutation::Delete => None,
+                },
+            },
+        )
+        .collect()
+}
+
+/// Every smallest set of `mutations`, at most `max_edits` of them and at most one per
+/// instruction, that makes the program meet `goal`. Sets of edits are tried breadth first, so
+/// that all the sets of one size are run before any larger one, which makes each extra edit
+/// allowed about as many times slower as the program is long.
+pub fn repair(
+    program: &[Instruction],
+    mutations: &[Mutation],
+    goal: &Goal,
+    max_edits: usize,
+) -> Vec<Repair> {
+    let mut level: Vec<Vec<Edit>> = vec![Vec::new()];
+
+    for _ in 0..=max_edits {
+        let repairs: Vec<Repair> = level
+            .iter()
+            .filter_map(|edits| {
+                let edited = apply(program, edits);
+                let mut console = Console::new(&edited);
+                let halt = console.run();
+                if goal.is_met(&halt, &console.state()) {
+                    Some(Repair {
+                        edits: edits.clone(),
+                        halt,
+                        state: console.state(),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if !repairs.is_empty() {
+            return repairs;
+        }
+
+        level = level
+            .iter()
+            .flat_map(|edits| {
+                let first_index = edits.last().map_or(0, |edit| edit.index + 1);
+                (first_index..program.len()).flat_map(move |index| {
+                    mutations
+                        .iter()
+                        .filter(move |mutation| mutation.applies_to(&program[index]))
+                        .map(move |mutation| {
+                            let mut edits = edits.clone();
+                            edits.push(Edit {
+                                index,
+                                mutation: *mutation,
+                            });
+                            edits
+                        })
+                })
+            })
+            .collect();
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use days::lib::handheld::{parse_program, TEST_CASE};
+
+    fn edit(index: usize, mutation: Mutation) -> Edit {
+        Edit { index, mutation }
+    }
+
+    #[test]
+    fn test_apply() {
+        let program = parse_program("nop +1\nacc -2\njmp +3").unwrap();
+
+        assert_eq!(program, apply(&program, &[]));
+        assert_eq!(
+            parse_program("jmp +1\nacc +2").unwrap(),
+            apply(
+                &program,
+                &[
+                    edit(0, Mutation::SwapOperation),
+                    edit(1, Mutation::NegateArgument),
+                    edit(2, Mutation::Delete)
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_repair_swap() {
+        let program = parse_program(TEST_CASE).unwrap();
+        let repairs = repair(&program, &[Mutation::SwapOperation], &Goal::Terminates, 1);
+
+        assert_eq!(
+            vec![Repair {
+                edits: vec![edit(7, Mutation::SwapOperation)],
+                halt: Halt::Terminated,
+                state: State {
+                    accumulator: 8,
+                    program_counter: 9
+                }
+            }],
+            repairs
+        );
+    }
+
+    #[test]
+    fn test_repair_minimal() {
+        let program = parse_program(TEST_CASE).unwrap();
+        let all = [
+            Mutation::SwapOperation,
+            Mutation::NegateArgument,
+            Mutation::Delete,
+        ];
+
+        let repairs = repair(&program, &all, &Goal::Terminates, 1);
+        assert_eq!(
+            vec![
+                vec![edit(7, Mutation::SwapOperation)],
+                vec![edit(7, Mutation::Delete)],
+            ],
+            repairs
+                .iter()
+                .map(|repair| repair.edits.clone())
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            vec![Vec::<Edit>::new()],
+            repair(&program, &all, &Goal::Accumulator(5), 2)
+                .into_iter()
+                .map(|repair| repair.edits)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_repair_goals() {
+        let program = parse_program("acc +1\njmp +0\nacc +2").unwrap();
+
+        let repairs = repair(&program, &[Mutation::Delete], &Goal::Accumulator(2), 2);
+        assert_eq!(
+            vec![vec![edit(0, Mutation::Delete), edit(1, Mutation::Delete)]],
+            repairs
+                .iter()
+                .map(|repair| repair.edits.clone())
+                .collect::<Vec<_>>()
+        );
+
+        let repairs = repair(
+            &program,
+            &[Mutation::SwapOperation, Mutation::NegateArgument],
+            &Goal::Predicate(|halt, state| *halt == Halt::Terminated && state.accumulator > 2),
+            1,
+        );
+        assert_eq!(1, repairs.len());
+        assert_eq!(vec![edit(1, Mutation::SwapOperation)], repairs[0].edits);
+        assert_eq!(3, repairs[0].state.accumulator);
+
+        assert!(repair(&program, &[Mutation::NegateArgument], &Goal::Terminates, 3).is_empty());
+    }
+}
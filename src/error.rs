@@ -0,0 +1,158 @@
+use std::fmt;
+use std::result;
+
+pub type Result<T> = result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Error {
+    /// Malformed puzzle input. `line` and `column` are 1-based.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A well-formed line of the puzzle input that can't be solved. `line` is 1-based.
+    Line {
+        line: usize,
+        message: String,
+    },
+    /// A problem with the input as a whole, such as a missing section.
+    Input(String),
+    Io {
+        source: String,
+        message: String,
+    },
+    /// A day's input file isn't where the input provider looked for it.
+    MissingInput {
+        day: usize,
+        path: String,
+    },
+}
+
+impl Error {
+    pub fn parse<S: Into<String>>(line: usize, column: usize, message: S) -> Self {
+        Error::Parse {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Builds a parse error pointing at `fragment`, which must be a slice of `input`.
+    pub fn at<S: Into<String>>(input: &str, fragment: &str, message: S) -> Self {
+        match position_of(input, fragment) {
+            Some((line, column)) => Error::parse(line, column, message),
+            None => Error::Input(message.into()),
+        }
+    }
+
+    pub fn line<S: Into<String>>(line: usize, message: S) -> Self {
+        Error::Line {
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub fn input<S: Into<String>>(message: S) -> Self {
+        Error::Input(message.into())
+    }
+
+    pub fn missing_input<S: Into<String>>(day: usize, path: S) -> Self {
+        Error::MissingInput {
+            day,
+            path: path.into(),
+        }
+    }
+
+    pub fn io<S: Into<String>>(source: S, error: &std::io::Error) -> Self {
+        Error::Io {
+            source: source.into(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+            Error::Line { line, message } => write!(f, "line {}: {}", line, message),
+            Error::Input(message) => write!(f, "{}", message),
+            Error::Io { source, message } => write!(f, "couldn't read {}: {}", source, message),
+            Error::MissingInput { day, path } => write!(
+                f,
+                "no input for day {}: {} doesn't exist (set the inputs directory with --inputs or AOC_INPUTS_DIR)",
+                day, path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn position_of(input: &str, fragment: &str) -> Option<(usize, usize)> {
+    let offset = (fragment.as_ptr() as usize).checked_sub(input.as_ptr() as usize)?;
+    if offset + fragment.len() > input.len() {
+        return None;
+    }
+    let before = input.get(..offset)?;
+
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let column = before[line_start..].chars().count() + 1;
+
+    Some((line, column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_at() {
+        let input = "nop +0\nacc +1\njmp x4";
+        let fragment = &input[18..];
+
+        assert_eq!("x4", fragment);
+        assert_eq!(
+            Error::parse(3, 5, "expected an integer"),
+            Error::at(input, fragment, "expected an integer")
+        );
+        assert_eq!(
+            Error::parse(1, 1, "empty"),
+            Error::at(input, &input[0..0], "empty")
+        );
+    }
+
+    #[test]
+    fn test_error_at_unrelated_fragment() {
+        assert_eq!(
+            Error::input("expected an integer"),
+            Error::at("abc", "def", "expected an integer")
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            "line 3, column 5: expected an integer",
+            Error::parse(3, 5, "expected an integer").to_string()
+        );
+        assert_eq!(
+            "line 2: 'x' isn't defined",
+            Error::line(2, "'x' isn't defined").to_string()
+        );
+        assert_eq!(
+            "expected your ticket section",
+            Error::input("expected your ticket section").to_string()
+        );
+        assert_eq!(
+            "no input for day 3: inputs/day3.txt doesn't exist (set the inputs directory with --inputs or AOC_INPUTS_DIR)",
+            Error::missing_input(3, "inputs/day3.txt").to_string()
+        );
+    }
+}
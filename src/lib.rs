@@ -4,4 +4,11 @@ extern crate scan_fmt;
 #[macro_use]
 extern crate lazy_static;
 
+pub mod answers;
+pub mod cli;
 pub mod days;
+pub mod error;
+pub mod output;
+pub mod repl;
+pub mod runner;
+pub mod scaffold;
@@ -1,57 +1,267 @@
 #![warn(clippy::all)]
-#[macro_use]
-extern crate scan_fmt;
+extern crate advent_of_code_2020;
 
-#[macro_use]
-extern crate lazy_static;
-
-mod days;
-use days::*;
+use advent_of_code_2020::answers::{Answers, Verdict, ANSWERS_FILENAME};
+use advent_of_code_2020::cli::{parse_command_line, Args, Command, DayRange, USAGE};
+use advent_of_code_2020::days::lib::recorder::AnimationFormat;
+use advent_of_code_2020::days::lib::{read_inputs, InputKind, InputProvider, InputSource};
+use advent_of_code_2020::days::{get_day, Day, DAYS};
+use advent_of_code_2020::error::Error;
+use advent_of_code_2020::output::{
+    format_results, format_run_summary, format_timings, OutputFormat,
+};
+use advent_of_code_2020::runner::{bench_day, run_day_from_source, run_days, DayResult};
+use advent_of_code_2020::scaffold::generate;
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+const LAST_DAY: usize = 25;
 
 fn main() {
-    let day = get_day();
-    let (part_one, part_two) = match day {
-        1 => day1::run(),
-        2 => day2::run(),
-        3 => day3::run(),
-        4 => day4::run(),
-        5 => day5::run(),
-        6 => day6::run(),
-        7 => day7::run(),
-        8 => day8::run(),
-        9 => day9::run(),
-        10 => day10::run(),
-        11 => day11::run(),
-        12 => day12::run(),
-        13 => day13::run(),
-        14 => day14::run(),
-        15 => day15::run(),
-        16 => day16::run(),
-        17 => day17::run(),
-        18 => day18::run(),
-        19 => day19::run(),
-        20 => day20::run(),
-        21 => day21::run(),
-        _ => (None, None),
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args = match parse_command_line(&args) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            process::exit(2);
+        }
     };
 
-    match (part_one, part_two) {
-        (None, None) => println!("Unknown day - {}", day),
-        (Some(part_one), None) => println!("# Day {}:\n  Part one: {}", day, part_one),
-        (None, Some(part_two)) => println!("# Day {}:\n  Part two: {}", day, part_two),
-        (Some(part_one), Some(part_two)) => println!(
-            "# Day {}:\n  Part one: {}\n  Part two: {}",
-            day, part_one, part_two
-        ),
+    if let Err(error) = run(args) {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let provider = InputProvider::locate(args.inputs_dir.as_deref());
+    let puzzle_input = InputSource::Puzzle(InputKind::Real);
+
+    match args.command {
+        Command::Run {
+            days,
+            part,
+            input,
+            format,
+            timings,
+            parallel,
+        } => {
+            let (results, wall_time) = run_days(&select_days(&days)?, parallel, |day| {
+                run_day_from_source(day, &input, &provider, part).map_err(|e| day_error(day, e))
+            });
+            let results = results.into_iter().collect::<Result<Vec<_>, String>>()?;
+            print_results(&results, format, timings);
+            if parallel && format == OutputFormat::Text {
+                print!("\n{}", format_run_summary(&results, wall_time));
+            }
+        }
+        Command::All {
+            part,
+            format,
+            timings,
+            parallel,
+        } => {
+            let days: Vec<&Day> = DAYS.iter().collect();
+            let (results, wall_time) = run_days(&days, parallel, |day| {
+                run_day_from_source(day, &puzzle_input, &provider, part)
+                    .map_err(|e| day_error(day, e))
+            });
+            let results = results.into_iter().collect::<Result<Vec<_>, String>>()?;
+            print_results(&results, format, timings);
+            if parallel && format == OutputFormat::Text {
+                print!("\n{}", format_run_summary(&results, wall_time));
+            }
+        }
+        Command::Bench { days, iterations } => {
+            for day in select_days(&days)? {
+                let inputs = puzzle_input
+                    .read(day.number, &provider)
+                    .map_err(|e| day_error(day, e))?;
+                let result = bench_day(day, &inputs, iterations).map_err(|e| day_error(day, e))?;
+                println!(
+                    "# Day {}: mean {:.2?}, min {:.2?}, max {:.2?} over {} iterations",
+                    result.day, result.mean, result.min, result.max, result.iterations
+                );
+            }
+        }
+        Command::Verify {
+            days,
+            answers,
+            parallel,
+        } => {
+            let answers = answers_filename(answers, &provider);
+            let expected = read_answers(&answers)?;
+            let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+            let days = select_optional_days(&days)?;
+            let (results, wall_time) = run_days(&days, parallel, |day| {
+                run_day_from_source(day, &puzzle_input, &provider, None)
+            });
+            for (day, result) in days.iter().zip(results) {
+                println!("# Day {}:", day.number);
+                let result = match result {
+                    Ok(result) => result,
+                    Err(error) => {
+                        println!("  error: {}", error);
+                        failed += 1;
+                        continue;
+                    }
+                };
+
+                for (part, verdict) in expected.verify(&result) {
+                    match verdict {
+                        Verdict::Pass => passed += 1,
+                        Verdict::Fail { .. } => failed += 1,
+                        Verdict::Missing => missing += 1,
+                    }
+                    println!("  Part {}: {}", part, verdict);
+                }
+            }
+
+            println!(
+                "{} passed, {} failed, {} missing in {} ms",
+                passed,
+                failed,
+                missing,
+                wall_time.as_millis()
+            );
+            if failed > 0 {
+                return Err(format!("{} answers don't match {}", failed, answers));
+            }
+        }
+        Command::Record { days, answers } => {
+            let answers = answers_filename(answers, &provider);
+            let mut recorded = if Path::new(&answers).exists() {
+                read_answers(&answers)?
+            } else {
+                Answers::default()
+            };
+
+            for day in select_optional_days(&days)? {
+                let result = run_day_from_source(day, &puzzle_input, &provider, None)
+                    .map_err(|e| day_error(day, e))?;
+                recorded.record(&result);
+            }
+
+            write_file(&answers, recorded.to_string())?;
+            println!("Recorded answers in {}", answers);
+        }
+        Command::Animate {
+            day,
+            part,
+            input,
+            format,
+            output,
+        } => {
+            let day = select_days(&DayRange {
+                first: day,
+                last: day,
+            })?[0];
+            let inputs = input
+                .read(day.number, &provider)
+                .map_err(|e| day_error(day, e))?;
+            let solution = day.parse(&inputs).map_err(|e| day_error(day, e))?;
+            let recorder = solution.animate(part).ok_or_else(|| {
+                format!("day {} doesn't have a simulation to animate", day.number)
+            })?;
+
+            match (format, output) {
+                (AnimationFormat::Ascii, None) => print!("{}", recorder.to_ascii()),
+                (AnimationFormat::Ascii, Some(output)) => write_file(&output, recorder.to_ascii())?,
+                (AnimationFormat::Ppm, output) => {
+                    let dir = output.unwrap_or_else(|| format!("day{}-frames", day.number));
+                    let frames = recorder
+                        .write_ppm_frames(Path::new(&dir))
+                        .map_err(|e| e.to_string())?;
+                    println!("Wrote {} frames to {}", frames.len(), dir);
+                }
+                (AnimationFormat::Gif, output) => {
+                    let output = output.unwrap_or_else(|| format!("day{}.gif", day.number));
+                    write_file(&output, recorder.to_gif().map_err(|e| e.to_string())?)?;
+                    println!("Wrote {} frames to {}", recorder.frames().len(), output);
+                }
+            }
+        }
+        Command::List => {
+            println!("Inputs in {}", provider.dir().display());
+            for number in 1..=LAST_DAY {
+                match get_day(number) {
+                    Some(_) => {
+                        let input_status = match (
+                            provider.path(number, InputKind::Real).is_file(),
+                            provider.path(number, InputKind::Example).is_file(),
+                        ) {
+                            (true, true) => "input, example",
+                            (true, false) => "input",
+                            (false, true) => "example, input missing",
+                            (false, false) => "input missing",
+                        };
+                        println!("Day {:>2}  implemented      {}", number, input_status);
+                    }
+                    None => println!("Day {:>2}  not implemented", number),
+                }
+            }
+        }
+        Command::New { day } => {
+            let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+            let created = generate(root, provider.dir(), day).map_err(|e| e.to_string())?;
+            for path in created {
+                println!("Wrote {}", path.display());
+            }
+        }
+        Command::Help => println!("{}", USAGE),
     };
+    Ok(())
+}
+
+fn select_days(days: &DayRange) -> Result<Vec<&'static Day>, String> {
+    let selected_days: Vec<&Day> = DAYS
+        .iter()
+        .filter(|day| days.contains(day.number))
+        .collect();
+
+    match (selected_days.len(), days.is_single_day()) {
+        (0, true) => Err(format!("day {} is not implemented", days.first)),
+        (0, false) => Err(format!(
+            "no days between {} and {} are implemented",
+            days.first, days.last
+        )),
+        _ => Ok(selected_days),
+    }
+}
+
+fn print_results(results: &[DayResult], format: OutputFormat, timings: bool) {
+    print!("{}", format_results(results, format));
+    if timings {
+        print!("\n{}", format_timings(results));
+    }
+}
+
+fn select_optional_days(days: &Option<DayRange>) -> Result<Vec<&'static Day>, String> {
+    match days {
+        Some(days) => select_days(days),
+        None => Ok(DAYS.iter().collect()),
+    }
+}
+
+fn answers_filename(answers: Option<String>, provider: &InputProvider) -> String {
+    answers.unwrap_or_else(|| provider.dir().join(ANSWERS_FILENAME).display().to_string())
+}
+
+fn read_answers(filename: &str) -> Result<Answers, String> {
+    let answers = read_inputs(filename).map_err(|error| error.to_string())?;
+    Answers::parse(&answers).map_err(|error| format!("{}: {}", filename, error))
+}
+
+fn write_file<C: AsRef<[u8]>>(filename: &str, contents: C) -> Result<(), String> {
+    fs::write(filename, contents).map_err(|error| format!("couldn't write {}: {}", filename, error))
 }
 
-fn get_day() -> i32 {
-    env::args()
-        .collect::<Vec<String>>()
-        .get(1)
-        .expect("Need to pass one argument as the day number. Example: cargo run 1")
-        .parse::<i32>()
-        .expect("Expecting first argument to be an integer")
+fn day_error(day: &Day, error: Error) -> String {
+    match error {
+        Error::MissingInput { .. } => error.to_string(),
+        error => format!("day {}: {}", day.number, error),
+    }
 }
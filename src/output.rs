@@ -0,0 +1,263 @@
+use runner::{critical_path, DayResult, PartResult};
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Renders the results of a run, one record per day and part for JSON and CSV.
+pub fn format_results(results: &[DayResult], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => results.iter().map(format_text).collect(),
+        OutputFormat::Json => format_json(results),
+        OutputFormat::Csv => format_csv(results),
+    }
+}
+
+/// A table of where the time went, costliest day first.
+pub fn format_timings(results: &[DayResult]) -> String {
+    let mut results: Vec<&DayResult> = results.iter().collect();
+    results.sort_by_key(|result| std::cmp::Reverse(result.total_time()));
+    let total_time: Duration = results.iter().map(|result| result.total_time()).sum();
+
+    let mut table = format!(
+        "{:>5} {:>12} {:>12} {:>12} {:>12} {:>12} {:>7}\n",
+        "Day", "Read (ms)", "Parse (ms)", "Part 1 (ms)", "Part 2 (ms)", "Total (ms)", "Share"
+    );
+    for result in results {
+        let solve_time = |part: &Option<PartResult>| match part {
+            Some(part) => milliseconds(part.solve_time),
+            None => "-".to_string(),
+        };
+        let share = if total_time > Duration::default() {
+            100.0 * result.total_time().as_secs_f64() / total_time.as_secs_f64()
+        } else {
+            0.0
+        };
+
+        table += &format!(
+            "{:>5} {:>12} {:>12} {:>12} {:>12} {:>12} {:>6.1}%\n",
+            result.day,
+            milliseconds(result.read_time),
+            milliseconds(result.parse_time),
+            solve_time(&result.part_one),
+            solve_time(&result.part_two),
+            milliseconds(result.total_time()),
+            share
+        );
+    }
+    table += &format!("{:>5} {:>64}\n", "Total", milliseconds(total_time));
+    table
+}
+
+/// One line comparing the wall-clock time of a run with the time spent in each day.
+pub fn format_run_summary(results: &[DayResult], wall_time: Duration) -> String {
+    let sequential_time: Duration = results.iter().map(|result| result.total_time()).sum();
+    let critical_path = match critical_path(results) {
+        Some(result) => format!(
+            "day {} ({} ms)",
+            result.day,
+            milliseconds(result.total_time())
+        ),
+        None => "none".to_string(),
+    };
+
+    let days = if results.len() == 1 { "day" } else { "days" };
+    format!(
+        "Ran {} {} in {} ms, {} ms of work, critical path {}\n",
+        results.len(),
+        days,
+        milliseconds(wall_time),
+        milliseconds(sequential_time),
+        critical_path
+    )
+}
+
+fn format_text(result: &DayResult) -> String {
+    let mut text = format!("# Day {}:\n", result.day);
+    for part in result.parts() {
+        let answer = part.answer.as_deref().unwrap_or("not solved");
+        text += &format!("  Part {}: {}\n", part.part, answer);
+    }
+    text
+}
+
+fn format_json(results: &[DayResult]) -> String {
+    let records: Vec<String> = records(results)
+        .map(|(result, part)| {
+            let answer = match &part.answer {
+                Some(answer) => json_string(answer),
+                None => "null".to_string(),
+            };
+            format!(
+                "  {{\"day\": {}, \"part\": {}, \"answer\": {}, \"read_time_ms\": {}, \"parse_time_ms\": {}, \"solve_time_ms\": {}}}",
+                result.day,
+                part.part.number(),
+                answer,
+                milliseconds(result.read_time),
+                milliseconds(result.parse_time),
+                milliseconds(part.solve_time)
+            )
+        })
+        .collect();
+
+    if records.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", records.join(",\n"))
+    }
+}
+
+fn format_csv(results: &[DayResult]) -> String {
+    let mut csv = "day,part,answer,read_time_ms,parse_time_ms,solve_time_ms\n".to_string();
+    for (result, part) in records(results) {
+        csv += &format!(
+            "{},{},{},{},{},{}\n",
+            result.day,
+            part.part.number(),
+            csv_field(part.answer.as_deref().unwrap_or("")),
+            milliseconds(result.read_time),
+            milliseconds(result.parse_time),
+            milliseconds(part.solve_time)
+        );
+    }
+    csv
+}
+
+fn records(results: &[DayResult]) -> impl Iterator<Item = (&DayResult, &PartResult)> {
+    results
+        .iter()
+        .flat_map(|result| result.parts().map(move |part| (result, part)))
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use days::lib::Part;
+
+    fn results() -> Vec<DayResult> {
+        vec![DayResult {
+            day: 21,
+            read_time: Duration::from_micros(100),
+            parse_time: Duration::from_micros(1500),
+            part_one: Some(PartResult {
+                part: Part::One,
+                answer: Some("2162".to_string()),
+                solve_time: Duration::from_micros(250),
+            }),
+            part_two: Some(PartResult {
+                part: Part::Two,
+                answer: None,
+                solve_time: Duration::from_millis(2),
+            }),
+        }]
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            "# Day 21:\n  Part one: 2162\n  Part two: not solved\n",
+            format_results(&results(), OutputFormat::Text)
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            "[
+  {\"day\": 21, \"part\": 1, \"answer\": \"2162\", \"read_time_ms\": 0.100, \"parse_time_ms\": 1.500, \"solve_time_ms\": 0.250},
+  {\"day\": 21, \"part\": 2, \"answer\": null, \"read_time_ms\": 0.100, \"parse_time_ms\": 1.500, \"solve_time_ms\": 2.000}
+]
+",
+            format_results(&results(), OutputFormat::Json)
+        );
+        assert_eq!("[]\n", format_results(&[], OutputFormat::Json));
+    }
+
+    #[test]
+    fn test_format_csv() {
+        assert_eq!(
+            "day,part,answer,read_time_ms,parse_time_ms,solve_time_ms
+21,1,2162,0.100,1.500,0.250
+21,2,,0.100,1.500,2.000
+",
+            format_results(&results(), OutputFormat::Csv)
+        );
+    }
+
+    #[test]
+    fn test_format_timings() {
+        let mut results = results();
+        results.push(DayResult {
+            day: 1,
+            read_time: Duration::from_micros(50),
+            parse_time: Duration::from_micros(100),
+            part_one: Some(PartResult {
+                part: Part::One,
+                answer: Some("1006875".to_string()),
+                solve_time: Duration::from_micros(50),
+            }),
+            part_two: None,
+        });
+
+        assert_eq!(
+            "  Day    Read (ms)   Parse (ms)  Part 1 (ms)  Part 2 (ms)   Total (ms)   Share
+   21        0.100        1.500        0.250        2.000        3.850   95.1%
+    1        0.050        0.100        0.050            -        0.200    4.9%
+Total                                                            4.050
+",
+            format_timings(&results)
+        );
+    }
+
+    #[test]
+    fn test_format_run_summary() {
+        assert_eq!(
+            "Ran 1 day in 3.000 ms, 3.850 ms of work, critical path day 21 (3.850 ms)\n",
+            format_run_summary(&results(), Duration::from_millis(3))
+        );
+        assert_eq!(
+            "Ran 0 days in 0.000 ms, 0.000 ms of work, critical path none\n",
+            format_run_summary(&[], Duration::default())
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!("\"a\\\"b\\\\c\"", json_string("a\"b\\c"));
+        assert_eq!("\"lmzg,cxk\"", csv_field("lmzg,cxk"));
+        assert_eq!("\"a\"\"b\"", csv_field("a\"b"));
+        assert_eq!("1006875", csv_field("1006875"));
+    }
+}
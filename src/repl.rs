@@ -0,0 +1,411 @@
+use days::day18::{self, Evaluation};
+use days::lib::expression::Precedence;
+use error::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+pub const HELP: &str = "Type an expression to evaluate it, or one of:
+    :mode [left-to-right|addition-first|standard]  show or change how operators bind
+    :let <name> = <expression>                     give a name to the value of an expression
+    :tree on|off                                   show the parse tree of each expression
+    :steps on|off                                  show each operation as it's worked out
+    :history                                       list the expressions evaluated so far
+    :help                                          show this message
+    :quit                                          leave";
+
+/// Which precedence rules expressions are evaluated with.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mode {
+    /// Addition and multiplication from left to right, as in the first part of day 18.
+    LeftToRight,
+    /// Addition before multiplication, as in the second part of day 18.
+    AdditionFirst,
+    /// Every operator, with the usual precedence.
+    Standard,
+}
+
+impl Mode {
+    pub fn from_name(mode: &str) -> Option<Self> {
+        match mode {
+            "left-to-right" => Some(Mode::LeftToRight),
+            "addition-first" => Some(Mode::AdditionFirst),
+            "standard" => Some(Mode::Standard),
+            _ => None,
+        }
+    }
+
+    pub fn precedence(&self) -> Precedence {
+        match self {
+            Mode::LeftToRight => Precedence::left_to_right(),
+            Mode::AdditionFirst => Precedence::addition_first(),
+            Mode::Standard => Precedence::standard(),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Mode::LeftToRight => "left-to-right",
+            Mode::AdditionFirst => "addition-first",
+            Mode::Standard => "standard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An expression that was evaluated, and what came of it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Entry {
+    pub expression: String,
+    pub mode: Mode,
+    pub result: Result<i64>,
+}
+
+/// The state of a session: the current settings, the variables defined with `:let`, and every
+/// expression evaluated so far.
+#[derive(Debug, Clone)]
+pub struct Repl {
+    mode: Mode,
+    show_tree: bool,
+    show_steps: bool,
+    variables: HashMap<String, i64>,
+    history: Vec<Entry>,
+}
+
+impl Repl {
+    pub fn new(mode: Mode) -> Self {
+        Repl {
+            mode,
+            show_tree: true,
+            show_steps: true,
+            variables: HashMap::new(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn history(&self) -> &[Entry] {
+        &self.history
+    }
+
+    /// Handles one line of input, returning what to print in reply, or `None` to quit.
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Some(String::new());
+        }
+        if !line.starts_with(':') {
+            return Some(self.evaluate(line));
+        }
+        if let Some(definition) = line.strip_prefix(":let ") {
+            return Some(self.define(definition));
+        }
+
+        let mut words = line.split_whitespace();
+        let reply = match (words.next(), words.next(), words.next()) {
+            (Some(":quit"), None, _) => return None,
+            (Some(":help"), None, _) => HELP.to_string(),
+            (Some(":mode"), None, _) => format!("mode: {}", self.mode),
+            (Some(":mode"), Some(mode), None) => match Mode::from_name(mode) {
+                Some(mode) => {
+                    self.mode = mode;
+                    format!("mode: {}", mode)
+                }
+                None => format!("error: unknown mode '{}'", mode),
+            },
+            (Some(":tree"), Some(setting), None) => match parse_setting(setting) {
+                Some(show_tree) => {
+                    self.show_tree = show_tree;
+                    format!("tree: {}", setting)
+                }
+                None => format!("error: expected 'on' or 'off', found '{}'", setting),
+            },
+            (Some(":steps"), Some(setting), None) => match parse_setting(setting) {
+                Some(show_steps) => {
+                    self.show_steps = show_steps;
+                    format!("steps: {}", setting)
+                }
+                None => format!("error: expected 'on' or 'off', found '{}'", setting),
+            },
+            (Some(":history"), None, _) => self.format_history(),
+            _ => format!("error: unknown command '{}' (try :help)", line),
+        };
+        Some(reply)
+    }
+
+    fn evaluate(&mut self, expression: &str) -> String {
+        let mut lines = Vec::new();
+        let result = day18::evaluate(expression, &self.mode.precedence(), &self.variables)
+            .and_then(|evaluation| {
+                if self.show_tree {
+                    lines.push(evaluation.tree.tree());
+                }
+                let (value, steps) = evaluation.result?;
+                if self.show_steps {
+                    lines.extend(steps);
+                }
+                Ok(value)
+            });
+        lines.push(match &result {
+            Ok(value) => format!("= {}", value),
+            Err(error) => format!("error: {}", error),
+        });
+
+        self.history.push(Entry {
+            expression: expression.to_string(),
+            mode: self.mode,
+            result,
+        });
+        lines.join("\n")
+    }
+
+    /// Handles `<name> = <expression>`, binding the name to the value of the expression.
+    fn define(&mut self, definition: &str) -> String {
+        let (name, expression) = match definition.split_once('=') {
+            Some((name, expression)) => (name.trim(), expression.trim()),
+            None => return "error: expected ':let <name> = <expression>'".to_string(),
+        };
+        if !is_name(name) {
+            return format!("error: expected a name, found '{}'", name);
+        }
+
+        match day18::evaluate(expression, &self.mode.precedence(), &self.variables)
+            .and_then(Evaluation::value)
+        {
+            Ok(value) => {
+                self.variables.insert(name.to_string(), value);
+                format!("{} = {}", name, value)
+            }
+            Err(error) => format!("error: {}", error),
+        }
+    }
+
+    fn format_history(&self) -> String {
+        if self.history.is_empty() {
+            return "no expressions yet".to_string();
+        }
+        self.history
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| {
+                let result = match &entry.result {
+                    Ok(value) => value.to_string(),
+                    Err(error) => format!("error: {}", error),
+                };
+                format!(
+                    "{}: {} ({}) = {}",
+                    idx + 1,
+                    entry.expression,
+                    entry.mode,
+                    result
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_setting(setting: &str) -> Option<bool> {
+    match setting {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Reads lines from `input` until it ends or `:quit`, writing a prompt before each one if
+/// `prompt` is set, and the reply to each one after it.
+pub fn run<R: BufRead, W: Write>(
+    mut repl: Repl,
+    input: R,
+    mut output: W,
+    prompt: bool,
+) -> io::Result<()> {
+    let mut lines = input.lines();
+    loop {
+        if prompt {
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+        match repl.handle(&line) {
+            Some(reply) if reply.is_empty() => {}
+            Some(reply) => writeln!(output, "{}", reply)?,
+            None => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error::Error;
+
+    #[test]
+    fn test_mode_from_name() {
+        assert_eq!(Some(Mode::LeftToRight), Mode::from_name("left-to-right"));
+        assert_eq!(Some(Mode::AdditionFirst), Mode::from_name("addition-first"));
+        assert_eq!(Some(Mode::Standard), Mode::from_name("standard"));
+        assert_eq!(None, Mode::from_name("weird"));
+        assert_eq!("addition-first", Mode::AdditionFirst.to_string());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut repl = Repl::new(Mode::LeftToRight);
+
+        assert_eq!(
+            Some("+\n  *\n    1\n    2\n  3\n1 * 2 = 2\n2 + 3 = 5\n= 5".to_string()),
+            repl.handle("1 * 2 + 3")
+        );
+        assert_eq!(Some("tree: off".to_string()), repl.handle(":tree off"));
+        assert_eq!(
+            Some("1 + 2 = 3\n3 * 4 = 12\n= 12".to_string()),
+            repl.handle("1 + 2 * 4")
+        );
+        assert_eq!(Some("steps: off".to_string()), repl.handle(":steps off"));
+        assert_eq!(
+            Some("error: line 1, column 3: expected '+', '*' or ')', found '-'".to_string()),
+            repl.handle("1 - 2")
+        );
+        assert_eq!(Some(String::new()), repl.handle("  "));
+    }
+
+    #[test]
+    fn test_modes() {
+        let mut repl = Repl::new(Mode::AdditionFirst);
+        repl.handle(":steps off");
+        repl.handle(":tree off");
+
+        assert_eq!(
+            Some("mode: addition-first".to_string()),
+            repl.handle(":mode")
+        );
+        assert_eq!(Some("= 9".to_string()), repl.handle("1 + 2 * 3"));
+        assert_eq!(
+            Some("mode: standard".to_string()),
+            repl.handle(":mode standard")
+        );
+        assert_eq!(Some("= 7".to_string()), repl.handle("1 + 2 * 3"));
+        assert_eq!(Some("= -6".to_string()), repl.handle("-2 ^ 3 + 2"));
+        assert_eq!(
+            Some("error: division by zero in '1 / 0'".to_string()),
+            repl.handle("1 / 0")
+        );
+        assert_eq!(
+            Some("error: unknown mode 'weird'".to_string()),
+            repl.handle(":mode weird")
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let mut repl = Repl::new(Mode::LeftToRight);
+        assert_eq!(
+            Some("no expressions yet".to_string()),
+            repl.handle(":history")
+        );
+
+        repl.handle("1 + 2 * 3");
+        repl.handle(":mode addition-first");
+        repl.handle("1 + 2 * 3");
+        repl.handle("1 +");
+
+        assert_eq!(
+            Some(
+                "1: 1 + 2 * 3 (left-to-right) = 9\n2: 1 + 2 * 3 (addition-first) = 9\n\
+                 3: 1 + (addition-first) = error: line 1, column 4: expected a number"
+                    .to_string()
+            ),
+            repl.handle(":history")
+        );
+        assert_eq!(
+            Entry {
+                expression: "1 +".to_string(),
+                mode: Mode::AdditionFirst,
+                result: Err(Error::parse(1, 4, "expected a number")),
+            },
+            repl.history()[2]
+        );
+    }
+
+    #[test]
+    fn test_let() {
+        let mut repl = Repl::new(Mode::LeftToRight);
+        repl.handle(":steps off");
+        repl.handle(":tree off");
+
+        assert_eq!(Some("x = 6".to_string()), repl.handle(":let x = 2 * 3"));
+        assert_eq!(Some("= 7".to_string()), repl.handle("x + 1"));
+        assert_eq!(
+            Some("x = 13".to_string()),
+            repl.handle(":let x = x * 2 + 1")
+        );
+        assert_eq!(
+            Some("error: 'y' isn't defined".to_string()),
+            repl.handle("x * y")
+        );
+        assert_eq!(
+            Some("error: line 1, column 3: expected '+', '*' or ')', found '^'".to_string()),
+            repl.handle(":let y = x ^ 2")
+        );
+        assert_eq!(
+            Some("error: expected a name, found '1x'".to_string()),
+            repl.handle(":let 1x = 2")
+        );
+        assert_eq!(
+            Some("error: expected ':let <name> = <expression>'".to_string()),
+            repl.handle(":let x 2")
+        );
+    }
+
+    #[test]
+    fn test_errors_after_parsing() {
+        let mut repl = Repl::new(Mode::Standard);
+
+        assert_eq!(
+            Some("/\n  1\n  0\nerror: division by zero in '1 / 0'".to_string()),
+            repl.handle("1 / 0")
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut repl = Repl::new(Mode::LeftToRight);
+
+        assert_eq!(Some(HELP.to_string()), repl.handle(":help"));
+        assert_eq!(
+            Some("error: expected 'on' or 'off', found 'yes'".to_string()),
+            repl.handle(":tree yes")
+        );
+        assert_eq!(
+            Some("error: unknown command ':undo' (try :help)".to_string()),
+            repl.handle(":undo")
+        );
+        assert_eq!(None, repl.handle(":quit"));
+    }
+
+    #[test]
+    fn test_run() {
+        let input = "1 + 2\n:history\n:quit\n3 + 4\n";
+        let mut output = Vec::new();
+        let mut repl = Repl::new(Mode::Standard);
+        repl.handle(":tree off");
+        repl.handle(":steps off");
+
+        run(repl, input.as_bytes(), &mut output, true).unwrap();
+        assert_eq!(
+            "> = 3\n> 1: 1 + 2 (standard) = 3\n> ",
+            String::from_utf8(output).unwrap()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+extern crate rayon;
+use self::rayon::prelude::*;
+
+use days::lib::{InputProvider, InputSource, Part};
+use days::Day;
+use error::Result;
+use std::time::{Duration, Instant};
+
+/// The outcome of one part. `answer` is `None` when the part isn't solved yet.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PartResult {
+    pub part: Part,
+    pub answer: Option<String>,
+    pub solve_time: Duration,
+}
+
+/// The outcome of one day. Parts that weren't requested are `None`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DayResult {
+    pub day: usize,
+    /// Zero when the input was already in memory.
+    pub read_time: Duration,
+    pub parse_time: Duration,
+    pub part_one: Option<PartResult>,
+    pub part_two: Option<PartResult>,
+}
+
+impl DayResult {
+    pub fn parts(&self) -> impl Iterator<Item = &PartResult> {
+        self.part_one.iter().chain(self.part_two.iter())
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.read_time + self.parse_time + self.parts().map(|part| part.solve_time).sum()
+    }
+}
+
+/// Like `run_day`, but reads the input first and times that too.
+pub fn run_day_from_source(
+    day: &Day,
+    input_source: &InputSource,
+    provider: &InputProvider,
+    part: Option<Part>,
+) -> Result<DayResult> {
+    let start = Instant::now();
+    let input = input_source.read(day.number, provider)?;
+    let read_time = start.elapsed();
+
+    let result = run_day(day, &input, part)?;
+    Ok(DayResult {
+        read_time,
+        ..result
+    })
+}
+
+/// Parses the input and solves the requested part, or both when `part` is `None`.
+pub fn run_day(day: &Day, input: &str, part: Option<Part>) -> Result<DayResult> {
+    let start = Instant::now();
+    let solution = day.parse(input)?;
+    let parse_time = start.elapsed();
+
+    let solve = |requested: Part, solve_fn: &dyn Fn() -> Result<Option<String>>| {
+        if part.is_some() && part != Some(requested) {
+            return Ok(None);
+        }
+        let start = Instant::now();
+        let answer = solve_fn()?;
+        Ok(Some(PartResult {
+            part: requested,
+            answer,
+            solve_time: start.elapsed(),
+        }))
+    };
+    let part_one = solve(Part::One, &|| solution.try_part_one())?;
+    let part_two = solve(Part::Two, &|| solution.try_part_two())?;
+
+    Ok(DayResult {
+        day: day.number,
+        read_time: Duration::default(),
+        parse_time,
+        part_one,
+        part_two,
+    })
+}
+
+/// Calls `run` for every day, concurrently on rayon's thread pool when `parallel` is set.
+/// The outputs keep the order of `days`; the duration is the wall-clock time of the whole run.
+pub fn run_days<T, F>(days: &[&'static Day], parallel: bool, run: F) -> (Vec<T>, Duration)
+where
+    T: Send,
+    F: Fn(&'static Day) -> T + Sync,
+{
+    let start = Instant::now();
+    let outputs = if parallel {
+        days.par_iter().map(|day| run(day)).collect()
+    } else {
+        days.iter().map(|day| run(day)).collect()
+    };
+    (outputs, start.elapsed())
+}
+
+/// The day that took the longest, which bounds how fast a parallel run can finish.
+pub fn critical_path(results: &[DayResult]) -> Option<&DayResult> {
+    results.iter().max_by_key(|result| result.total_time())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BenchResult {
+    pub day: usize,
+    pub iterations: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+pub fn bench_day(day: &Day, input: &str, iterations: usize) -> Result<BenchResult> {
+    let durations = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            run_day(day, input, None)?;
+            Ok(start.elapsed())
+        })
+        .collect::<Result<Vec<Duration>>>()?;
+
+    Ok(BenchResult {
+        day: day.number,
+        iterations,
+        min: *durations.iter().min().unwrap(),
+        mean: durations.iter().sum::<Duration>() / iterations as u32,
+        max: *durations.iter().max().unwrap(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use days::lib::InputKind;
+    use days::{get_day, DAYS};
+    use error::Error;
+
+    #[test]
+    fn test_run_day() {
+        let day = get_day(1).unwrap();
+        let input = "1721\n979\n366\n299\n675\n1456";
+
+        let result = run_day(day, input, None).unwrap();
+        let answers: Vec<_> = result
+            .parts()
+            .map(|part| (part.part, part.answer.as_deref()))
+            .collect();
+
+        assert_eq!(1, result.day);
+        assert_eq!(
+            vec![(Part::One, Some("514579")), (Part::Two, Some("241861950"))],
+            answers
+        );
+        assert_eq!(None, run_day(day, input, Some(Part::One)).unwrap().part_two);
+        assert_eq!(None, run_day(day, input, Some(Part::Two)).unwrap().part_one);
+    }
+
+    #[test]
+    fn test_run_day_errors_when_solving() {
+        let day = get_day(18).unwrap();
+        let input = "3037000500 * 3037000499 + 2";
+
+        assert_eq!(
+            Some(Error::line(1, "'3037000500 * 3037000501' overflows")),
+            run_day(day, input, None).err()
+        );
+        assert!(run_day(day, input, Some(Part::One)).is_ok());
+    }
+
+    #[test]
+    fn test_run_day_from_source() {
+        let day = get_day(1).unwrap();
+        let provider = InputProvider::locate(None);
+        let input_source = InputSource::Puzzle(InputKind::Real);
+        let result = run_day_from_source(day, &input_source, &provider, Some(Part::One)).unwrap();
+
+        assert!(result.part_one.is_some());
+        assert!(result.part_two.is_none());
+        assert!(result.read_time > Duration::default());
+        assert_eq!(
+            result.read_time + result.parse_time + result.part_one.as_ref().unwrap().solve_time,
+            result.total_time()
+        );
+
+        let missing_input = InputSource::File("inputs/missing.txt".to_string());
+        assert!(run_day_from_source(day, &missing_input, &provider, None).is_err());
+    }
+
+    #[test]
+    fn test_run_days() {
+        let days: Vec<&Day> = DAYS.iter().take(5).collect();
+        let provider = InputProvider::locate(None);
+        let run = |day: &'static Day| {
+            let input_source = InputSource::Puzzle(InputKind::Real);
+            run_day_from_source(day, &input_source, &provider, None).unwrap()
+        };
+
+        let (sequential, _) = run_days(&days, false, run);
+        let (parallel, _) = run_days(&days, true, run);
+        let answers = |results: &[DayResult]| -> Vec<(usize, Option<String>, Option<String>)> {
+            results
+                .iter()
+                .map(|result| {
+                    let answer = |part: &Option<PartResult>| part.as_ref().unwrap().answer.clone();
+                    (
+                        result.day,
+                        answer(&result.part_one),
+                        answer(&result.part_two),
+                    )
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            vec![1, 2, 3, 4, 5],
+            parallel.iter().map(|r| r.day).collect::<Vec<_>>()
+        );
+        assert_eq!(answers(&sequential), answers(&parallel));
+        assert_eq!(
+            Some(parallel.iter().map(|r| r.total_time()).max().unwrap()),
+            critical_path(&parallel).map(|r| r.total_time())
+        );
+    }
+
+    #[test]
+    fn test_run_day_with_invalid_input() {
+        let day = get_day(1).unwrap();
+        assert!(run_day(day, "1721\nabc", None).is_err());
+    }
+
+    #[test]
+    fn test_bench_day() {
+        let day = get_day(1).unwrap();
+        let result = bench_day(day, "1721\n979\n366\n299\n675\n1456", 3).unwrap();
+
+        assert_eq!(3, result.iterations);
+        assert!(result.min <= result.mean && result.mean <= result.max);
+    }
+}
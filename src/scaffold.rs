@@ -0,0 +1,206 @@
+use error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MODULE_TEMPLATE: &str = "use super::lib::*;
+
+pub struct Day__ {}
+
+impl Solution for Day__ {
+    fn parse(_input: &str) -> Result<Self> {
+        Ok(Day__ {})
+    }
+
+    fn part_one(&self) -> Option<String> {
+        None
+    }
+
+    fn part_two(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CASE_1: &str = \"\";
+
+    #[test]
+    fn test_part_one() {
+        let day = Day__::parse(TEST_CASE_1).unwrap();
+        assert_eq!(None, day.part_one());
+    }
+
+    #[test]
+    fn test_part_two() {
+        let day = Day__::parse(TEST_CASE_1).unwrap();
+        assert_eq!(None, day.part_two());
+    }
+}
+";
+
+/// The source of a new, unsolved day with an empty sample input for its tests.
+pub fn module_source(day: usize) -> String {
+    MODULE_TEMPLATE.replace("Day__", &format!("Day{}", day))
+}
+
+/// Adds `day` to the `mod` declarations and to `DAYS` in the source of `days.rs`.
+pub fn register_day(days_source: &str, day: usize) -> Result<String> {
+    let mod_line = format!("pub mod day{};", day);
+    if days_source.lines().any(|line| line == mod_line) {
+        return Err(Error::input(format!("day {} is already registered", day)));
+    }
+
+    let mut lines: Vec<String> = days_source.lines().map(|line| line.to_string()).collect();
+
+    // The declarations are kept in the order rustfmt sorts them in.
+    let module = format!("day{}", day);
+    let mod_idx = lines
+        .iter()
+        .position(|line| module_name(line).is_some_and(|name| name > module.as_str()))
+        .ok_or_else(|| Error::input("expected the day modules to come before `pub mod lib;`"))?;
+    lines.insert(mod_idx, mod_line);
+
+    let days_start = lines
+        .iter()
+        .position(|line| line.starts_with("pub const DAYS"))
+        .ok_or_else(|| Error::input("expected a `DAYS` list"))?;
+    let days_end = days_start
+        + lines[days_start..]
+            .iter()
+            .position(|line| line == "];")
+            .ok_or_else(|| Error::input("expected the `DAYS` list to end with `];`"))?;
+    let entry_idx = (days_start + 1..days_end)
+        .find(|idx| registered_day(&lines[*idx]).is_some_and(|number| number > day))
+        .unwrap_or(days_end);
+    lines.insert(
+        entry_idx,
+        format!("    Day::new({}, parse::<day{}::Day{}>),", day, day, day),
+    );
+
+    Ok(lines.join("\n") + "\n")
+}
+
+fn module_name(line: &str) -> Option<&str> {
+    line.strip_prefix("pub mod ")?.strip_suffix(';')
+}
+
+fn registered_day(line: &str) -> Option<usize> {
+    line.trim()
+        .strip_prefix("Day::new(")?
+        .split(',')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Creates the module, registers it and adds empty input files for `day`, returning what was
+/// created. `root` is the crate directory and `inputs_dir` the puzzle inputs directory.
+pub fn generate(root: &Path, inputs_dir: &Path, day: usize) -> Result<Vec<PathBuf>> {
+    let module_path = root.join("src/days").join(format!("day{}.rs", day));
+    if module_path.exists() {
+        return Err(Error::input(format!(
+            "{} already exists",
+            module_path.display()
+        )));
+    }
+
+    let days_path = root.join("src/days.rs");
+    let days_source = read(&days_path)?;
+    let days_source = register_day(&days_source, day)?;
+
+    write(&module_path, &module_source(day))?;
+    write(&days_path, &days_source)?;
+    let mut created = vec![module_path, days_path];
+
+    for filename in &[format!("day{}.txt", day), format!("day{}.example.txt", day)] {
+        let input_path = inputs_dir.join(filename);
+        if !input_path.exists() {
+            write(&input_path, "")?;
+            created.push(input_path);
+        }
+    }
+
+    Ok(created)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|error| Error::io(path.display().to_string(), &error))
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).map_err(|error| Error::io(path.display().to_string(), &error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAYS_SOURCE: &str = "pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day3;
+pub mod lib;
+
+pub const DAYS: &[Day] = &[
+    Day::new(1, parse::<day1::Day1>),
+    Day::new(2, parse::<day2::Day2>),
+    Day::new(3, parse::<day3::Day3>),
+    Day::new(10, parse::<day10::Day10>),
+];
+";
+
+    #[test]
+    fn test_module_source() {
+        let source = module_source(22);
+
+        assert!(source.contains("pub struct Day22 {}"));
+        assert!(source.contains("impl Solution for Day22 {"));
+        assert!(source.contains("let day = Day22::parse(TEST_CASE_1).unwrap();"));
+        assert!(!source.contains("Day__"));
+    }
+
+    #[test]
+    fn test_register_day() {
+        assert_eq!(
+            Ok("pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day22;
+pub mod day3;
+pub mod lib;
+
+pub const DAYS: &[Day] = &[
+    Day::new(1, parse::<day1::Day1>),
+    Day::new(2, parse::<day2::Day2>),
+    Day::new(3, parse::<day3::Day3>),
+    Day::new(10, parse::<day10::Day10>),
+    Day::new(22, parse::<day22::Day22>),
+];
+"
+            .to_string()),
+            register_day(DAYS_SOURCE, 22)
+        );
+
+        let registered = register_day(DAYS_SOURCE, 4).unwrap();
+        assert!(registered.contains("pub mod day3;\npub mod day4;\npub mod lib;"));
+        assert!(registered.contains(
+            "Day::new(3, parse::<day3::Day3>),\n    Day::new(4, parse::<day4::Day4>),\n    Day::new(10,"
+        ));
+    }
+
+    #[test]
+    fn test_register_day_twice() {
+        assert_eq!(
+            Err(Error::input("day 2 is already registered")),
+            register_day(DAYS_SOURCE, 2)
+        );
+    }
+
+    #[test]
+    fn test_register_current_days() {
+        let days_source = read(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src/days.rs")).unwrap();
+        assert!(register_day(&days_source, 25).is_ok());
+    }
+}